@@ -1,25 +1,138 @@
-use colored::Colorize; // pozn. z prednasky - neimportovat cely crate (::*), ale jen to, co chci pouzit
+use colored::{Color, Colorize}; // pozn. z prednasky - neimportovat cely crate (::*), ale jen to, co chci pouzit
+use std::env;
 use std::io;
+use std::process;
+
+// Colors accepted by --color, the default first
+const COLOR_NAMES: [&str; 16] = [
+    "bright-red",
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+// Maps a name from COLOR_NAMES, in any case, onto a color of the colored crate
+fn parse_color(name: &str) -> Option<Color> {
+    let name = name.to_lowercase();
+    if !COLOR_NAMES.contains(&name.as_str()) {
+        return None;
+    }
+    // colored names the bright colors with a space, e.g. "bright red"
+    name.replace('-', " ").parse().ok()
+}
+
+// Reads the greeting color from `--color <name>` or `--color=<name>`, falling back to bright red
+fn color_from_args(args: &[String]) -> Color {
+    let name = args
+        .iter()
+        .enumerate()
+        .find_map(|(e, arg)| match arg.strip_prefix("--color") {
+            Some("") => args.get(e + 1).map(String::as_str),
+            Some(value) => value.strip_prefix('='),
+            None => None,
+        });
+
+    match name.map(|name| (name, parse_color(name))) {
+        Some((_, Some(color))) => color,
+        Some((name, None)) => {
+            eprintln!(
+                "Unknown color '{}', available colors: {}",
+                name,
+                COLOR_NAMES.join(", ")
+            );
+            Color::BrightRed
+        }
+        None => Color::BrightRed,
+    }
+}
+
+// Returns the trimmed name, or None when nothing but whitespace was entered
+fn validate_name(input: &str) -> Option<String> {
+    let name = input.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
 
 fn get_user_input(prompt: &str) -> String {
     println!("{}", prompt);
 
-    let mut input = String::new();
+    loop {
+        let mut input = String::new();
 
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
+        let read = io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
 
-    input.trim().to_string()
+        // End of input, e.g. Ctrl+D - asking again would loop forever
+        if read == 0 {
+            eprintln!("No name entered");
+            process::exit(1);
+        }
+
+        match validate_name(&input) {
+            Some(name) => return name,
+            None => println!("{}", "The name can't be empty, please try again".yellow()),
+        }
+    }
 }
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let color = color_from_args(&args);
+
     let name = get_user_input("Please, enter your name");
 
     let greeting = format!("Hello, {}", name)
-        .bright_red()
+        .color(color)
         .on_bright_white()
         .bold();
 
     println!("{}", greeting);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("  Alice \n"), Some("Alice".to_string()));
+        assert_eq!(validate_name("\n"), None);
+        assert_eq!(validate_name(" \t "), None);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("green"), Some(Color::Green));
+        assert_eq!(parse_color("Bright-Blue"), Some(Color::BrightBlue));
+        assert_eq!(parse_color("bright blue"), None);
+        assert_eq!(parse_color("purple"), None);
+    }
+
+    #[test]
+    fn test_color_from_args_falls_back_to_default() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(color_from_args(&args(&["--color", "cyan"])), Color::Cyan);
+        assert_eq!(color_from_args(&args(&["--color=yellow"])), Color::Yellow);
+        assert_eq!(
+            color_from_args(&args(&["--color", "purple"])),
+            Color::BrightRed
+        );
+        assert_eq!(color_from_args(&args(&[])), Color::BrightRed);
+    }
+}
@@ -0,0 +1,78 @@
+// Throughput of the text modifiers on a megabyte of generated input. Run with `cargo bench`;
+// criterion reports the bytes per second each modifier gets through.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lesson_02::{execute_operation, Modifier};
+
+// Size of the generated input of every benchmark
+const INPUT_LEN: usize = 1024 * 1024;
+
+// Words the dataset is made of, mixing cases, punctuation and a non-ASCII word so the modifiers
+// take all of their branches
+const WORDS: [&str; 8] = [
+    "Lorem",
+    "ipsum",
+    "DOLOR",
+    "sit",
+    "amet,",
+    "consectetur",
+    "Žluťoučký",
+    "kůň!",
+];
+
+// Generates at least `len` bytes of text by cycling through WORDS. The output is the same on every
+// run, so results of different runs can be compared.
+fn generate_text(len: usize) -> String {
+    let mut text = String::with_capacity(len + 16);
+    for word in WORDS.iter().cycle() {
+        if text.len() >= len {
+            break;
+        }
+        text.push_str(word);
+        text.push(' ');
+    }
+    text
+}
+
+// Generates at least `len` bytes of ';'-separated CSV with a header row and four columns
+fn generate_csv(len: usize) -> String {
+    let mut csv = String::from("id;name;city;amount\n");
+    let mut id = 0;
+    while csv.len() < len {
+        let word = WORDS[id % WORDS.len()];
+        csv.push_str(&format!(
+            "{};{};{};{}\n",
+            id,
+            word,
+            word.to_uppercase(),
+            id * 7
+        ));
+        id += 1;
+    }
+    csv
+}
+
+fn bench_modifiers(c: &mut Criterion) {
+    let text = generate_text(INPUT_LEN);
+    let csv = generate_csv(INPUT_LEN);
+
+    let mut group = c.benchmark_group("modifiers");
+    for (name, input) in [("rot13", &text), ("slugify", &text), ("csv", &csv)] {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), input, |b, input| {
+            // Parsing the modifier is negligible next to transforming a megabyte
+            b.iter(|| {
+                let modifier: Modifier = name.parse().unwrap();
+                execute_operation(modifier, black_box(input)).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // A megabyte per iteration is slow enough that fewer samples still give stable results
+    config = Criterion::default().sample_size(20);
+    targets = bench_modifiers
+}
+criterion_main!(benches);
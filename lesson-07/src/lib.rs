@@ -0,0 +1,1950 @@
+// Text modifiers shared by the CLI in main.rs and any other binary: parse a Modifier from its
+// name and apply it with transform, or chain several with execute_pipeline.
+use colored::Colorize;
+use csv::{ReaderBuilder, WriterBuilder};
+use regex::Regex;
+use slug::slugify;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::{error::Error, fmt, iter};
+use unicode_categories::UnicodeCategories;
+use unicode_width::UnicodeWidthStr;
+
+// Custom Error type for the operations
+#[derive(Debug)]
+pub struct OperationError(pub String);
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation Error: {}", self.0)
+    }
+}
+
+impl Error for OperationError {}
+
+// Aggregation of the rows of a group in Csv::group_by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Agg {
+    Sum,
+    Count,
+    Mean,
+}
+
+impl Agg {
+    fn name(self) -> &'static str {
+        match self {
+            Agg::Sum => "sum",
+            Agg::Count => "count",
+            Agg::Mean => "mean",
+        }
+    }
+}
+
+impl FromStr for Agg {
+    type Err = OperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sum" => Ok(Agg::Sum),
+            "count" => Ok(Agg::Count),
+            "mean" => Ok(Agg::Mean),
+            _ => Err(OperationError(format!(
+                "Unknown aggregation '{}', use sum, count or mean",
+                s
+            ))),
+        }
+    }
+}
+
+// Csv struct to store headers and rows
+pub struct Csv {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Csv {
+    // Renders the table like Display, but with a bold header row and a dimmed separator line.
+    // Meant for terminals: callers should check that stdout is a TTY, so piped output stays clean.
+    pub fn to_string_colored(&self) -> String {
+        let mut table = String::new();
+        self.write_table(&mut table, true)
+            .expect("Writing to a String can't fail");
+        table
+    }
+
+    // Serializes the table back to CSV text with the given delimiter, headers first. Fields
+    // containing the delimiter, quotes or line breaks are quoted.
+    pub fn to_csv_string(&self, delimiter: u8) -> Result<String, Box<dyn Error>> {
+        let mut writer = WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(Vec::new());
+        writer.write_record(&self.headers)?;
+        for row in &self.rows {
+            writer.write_record(row)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    // Turns rows into columns: the headers become the first column and every row one of the
+    // following columns. Rows shorter than the widest row are padded with empty cells first.
+    pub fn transpose(&self) -> Csv {
+        let width = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain(iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0);
+        let cell = |row: &[String], e: usize| row.get(e).cloned().unwrap_or_default();
+
+        let mut columns = (0..width).map(|e| {
+            iter::once(cell(&self.headers, e))
+                .chain(self.rows.iter().map(|row| cell(row, e)))
+                .collect::<Vec<_>>()
+        });
+        Csv {
+            headers: columns.next().unwrap_or_default(),
+            rows: columns.collect(),
+        }
+    }
+
+    // Groups the rows by the value in column key_col and aggregates column agg_col of every group,
+    // returning a table with one row per group, in order of first appearance. Columns count from
+    // 0. Count counts the rows of a group; sum and mean fail on cells that aren't numbers.
+    pub fn group_by(
+        &self,
+        key_col: usize,
+        agg_col: usize,
+        agg: Agg,
+    ) -> Result<Csv, OperationError> {
+        for column in [key_col, agg_col] {
+            if column >= self.headers.len() {
+                return Err(OperationError(format!(
+                    "No column {}, the table has {} columns",
+                    column + 1,
+                    self.headers.len()
+                )));
+            }
+        }
+        let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+        let mut group_of_key: HashMap<&str, usize> = HashMap::new();
+        for row in &self.rows {
+            let key = row.get(key_col).map_or("", String::as_str);
+            let group = *group_of_key.entry(key).or_insert_with(|| {
+                groups.push((key, Vec::new()));
+                groups.len() - 1
+            });
+            groups[group]
+                .1
+                .push(row.get(agg_col).map_or("", String::as_str));
+        }
+
+        let agg_header = &self.headers[agg_col];
+        let rows = groups
+            .into_iter()
+            .map(|(key, values)| {
+                let aggregate = match agg {
+                    Agg::Count => values.len().to_string(),
+                    Agg::Sum | Agg::Mean => {
+                        let numbers = values
+                            .iter()
+                            .map(|value| {
+                                value.trim().parse::<f64>().map_err(|_| {
+                                    OperationError(format!(
+                                        "Can't {} '{}' in column '{}', it isn't a number",
+                                        agg.name(),
+                                        value,
+                                        agg_header
+                                    ))
+                                })
+                            })
+                            .collect::<Result<Vec<f64>, _>>()?;
+                        let sum: f64 = numbers.iter().sum();
+                        if agg == Agg::Mean {
+                            (sum / numbers.len() as f64).to_string()
+                        } else {
+                            sum.to_string()
+                        }
+                    }
+                };
+                Ok(vec![key.to_string(), aggregate])
+            })
+            .collect::<Result<Vec<_>, OperationError>>()?;
+
+        Ok(Csv {
+            headers: vec![
+                self.headers[key_col].clone(),
+                format!("{}({})", agg.name(), agg_header),
+            ],
+            rows,
+        })
+    }
+
+    // Summarizes every column in a row of count, min, max, mean and sample standard deviation.
+    // A column is numeric if every non-empty cell is a number; for other columns, and for the
+    // standard deviation of a single number, only the cells that apply are filled in. Count
+    // counts the non-empty cells.
+    pub fn describe(&self) -> Csv {
+        let headers = ["column", "count", "min", "max", "mean", "stddev"]
+            .iter()
+            .map(|header| header.to_string())
+            .collect();
+
+        let rows = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(e, header)| {
+                let cells: Vec<&str> = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(e).map(|cell| cell.trim()))
+                    .filter(|cell| !cell.is_empty())
+                    .collect();
+                let count = cells.len();
+                let mut summary = vec![header.clone(), count.to_string()];
+
+                let numbers = cells
+                    .iter()
+                    .map(|cell| cell.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>();
+                match numbers {
+                    Ok(numbers) if !numbers.is_empty() => {
+                        let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+                        let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                        let mean = numbers.iter().sum::<f64>() / count as f64;
+                        let stddev = (count > 1).then(|| {
+                            let squares: f64 = numbers.iter().map(|x| (x - mean).powi(2)).sum();
+                            (squares / (count - 1) as f64).sqrt()
+                        });
+                        summary.extend([min, max, mean].map(format_stat));
+                        summary.push(stddev.map(format_stat).unwrap_or_default());
+                    }
+                    _ => summary.extend(vec![String::new(); 4]),
+                }
+                summary
+            })
+            .collect();
+
+        Csv { headers, rows }
+    }
+
+    // Inner join: pairs every row with the rows of other having the same value in the join
+    // columns, which count from 0. A key occurring several times on both sides yields every
+    // combination of its rows, like in SQL. The result has the columns of self followed by those
+    // of other without its join column, and keeps the row order of self, then of other.
+    pub fn join(&self, other: &Csv, left_col: usize, right_col: usize) -> Csv {
+        let cell = |row: &[String], e: usize| row.get(e).cloned().unwrap_or_default();
+        let without_key = |row: &[String]| {
+            row.iter()
+                .enumerate()
+                .filter(|&(e, _)| e != right_col)
+                .map(|(_, field)| field.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut rows_of_key: HashMap<String, Vec<&Vec<String>>> = HashMap::new();
+        for row in &other.rows {
+            rows_of_key
+                .entry(cell(row, right_col))
+                .or_default()
+                .push(row);
+        }
+
+        let rows = self
+            .rows
+            .iter()
+            .flat_map(|left| {
+                let matches = rows_of_key.get(&cell(left, left_col));
+                matches.into_iter().flatten().map(|right| {
+                    let mut joined = left.clone();
+                    joined.extend(without_key(right));
+                    joined
+                })
+            })
+            .collect();
+
+        let mut headers = self.headers.clone();
+        headers.extend(without_key(&other.headers));
+        Csv { headers, rows }
+    }
+
+    // Renders the table as an HTML <table>, the headers as <th> cells in the <thead> and the rows
+    // as <td> cells in the <tbody>, ready to be pasted into a web page
+    pub fn to_html(&self) -> String {
+        let html_row = |row: &[String], tag: &str| {
+            let cells: String = row
+                .iter()
+                .map(|cell| format!("<{0}>{1}</{0}>", tag, escape_html(cell)))
+                .collect();
+            format!("<tr>{}</tr>", cells)
+        };
+
+        let mut html = String::from("<table>\n  <thead>\n");
+        html.push_str(&format!("    {}\n", html_row(&self.headers, "th")));
+        html.push_str("  </thead>\n  <tbody>\n");
+        for row in &self.rows {
+            html.push_str(&format!("    {}\n", html_row(row, "td")));
+        }
+        html.push_str("  </tbody>\n</table>");
+        html
+    }
+
+    fn write_table<W: fmt::Write>(&self, out: &mut W, colored: bool) -> fmt::Result {
+        // Calculate maximum width for each column, in terminal columns rather than bytes so
+        // accented and wide (e.g. CJK) characters line up
+        let max_widths: Vec<usize> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(e, header)| {
+                iter::once(header.width())
+                    .chain(self.rows.iter().map(|row| row[e].width()))
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+
+        // Right-align columns where every data cell is a number
+        let numeric: Vec<bool> = (0..self.headers.len())
+            .map(|e| {
+                !self.rows.is_empty()
+                    && self
+                        .rows
+                        .iter()
+                        .all(|row| row[e].trim().parse::<f64>().is_ok())
+            })
+            .collect();
+
+        // Display headers, always left-aligned
+        let left_aligned = vec![false; max_widths.len()];
+        write_row(out, &self.headers, &max_widths, &left_aligned, colored)?;
+
+        // Display separator line
+        write_separator(out, &max_widths, colored)?;
+
+        // Dispaly rows
+        for row in &self.rows {
+            write_row(out, row, &max_widths, &numeric, false)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Implementing the Display trait for Csv from: https://doc.rust-lang.org/std/fmt/trait.Display.html#examples
+impl fmt::Display for Csv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_table(f, false)
+    }
+}
+
+fn write_row<W: fmt::Write>(
+    out: &mut W,
+    row: &[String],
+    max_widths: &[usize],
+    right_aligned: &[bool],
+    bold: bool,
+) -> fmt::Result {
+    write!(out, "| ")?;
+    for ((field, &width), &right) in row.iter().zip(max_widths).zip(right_aligned) {
+        // Pad manually, as the formatter pads by char count instead of display width
+        let padding = width.saturating_sub(field.width());
+        let field = if bold { field.bold() } else { field.normal() };
+        if right {
+            write!(out, "{:padding$}{} | ", "", field, padding = padding)?;
+        } else {
+            write!(out, "{}{:padding$} | ", field, "", padding = padding)?;
+        }
+    }
+    writeln!(out)
+}
+
+// Formats a statistic of Csv::describe with at most 3 decimal places, e.g. 2.5 or 0.333
+fn format_stat(value: f64) -> String {
+    let formatted = format!("{:.3}", value);
+    match formatted.trim_end_matches('0').trim_end_matches('.') {
+        // Tiny negative values round to zero
+        "-0" => "0".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+// Escapes the characters with a meaning in HTML text, so cells are shown as written
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn write_separator<W: fmt::Write>(out: &mut W, max_widths: &[usize], dimmed: bool) -> fmt::Result {
+    let mut line = String::from("|");
+    for &width in max_widths {
+        line.push_str(&format!("{:-<width$}|", "", width = width + 2));
+    }
+    if dimmed {
+        writeln!(out, "{}", line.dimmed())
+    } else {
+        writeln!(out, "{}", line)
+    }
+}
+
+// International Morse code for letters and digits
+const MORSE_CODE: [(char, &str); 36] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+];
+
+// Largest count of the repeat modifier, so a typo can't exhaust the memory
+const MAX_REPEAT: usize = 10_000;
+
+// Largest integer num2words spells out
+const MAX_NUM_WORDS: u64 = 999_999_999;
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+// Spells out a number below 1000, e.g. "three hundred forty-two"
+fn hundreds_to_words(n: u64) -> String {
+    let mut words = Vec::new();
+    if n >= 100 {
+        words.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    match n % 100 {
+        0 if n >= 100 => {}
+        rest @ 0..=19 => words.push(ONES[rest as usize].to_string()),
+        rest if rest % 10 == 0 => words.push(TENS[(rest / 10) as usize].to_string()),
+        rest => words.push(format!(
+            "{}-{}",
+            TENS[(rest / 10) as usize],
+            ONES[(rest % 10) as usize]
+        )),
+    }
+    words.join(" ")
+}
+
+fn number_to_words(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut words = Vec::new();
+    for (scale, name) in [(1_000_000, " million"), (1_000, " thousand"), (1, "")] {
+        let group = n / scale % 1000;
+        if group > 0 {
+            words.push(format!("{}{}", hundreds_to_words(group), name));
+        }
+    }
+    words.join(" ")
+}
+
+// Roman numeral symbols, including the subtractive pairs, from the largest value down
+const ROMAN_NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+// Converts a single word of letters to pig latin. A capitalized word stays capitalized, e.g.
+// Hello -> Ellohay.
+fn pig_latin_word(word: &str) -> String {
+    let is_vowel = |c: char| "aeiouAEIOU".contains(c);
+    // A 'y' after the first letter sounds like a vowel, e.g. rhythm -> ythmrhay
+    let mut split = word
+        .char_indices()
+        .find(|&(i, c)| is_vowel(c) || (i > 0 && (c == 'y' || c == 'Y')))
+        .map_or(word.len(), |(i, _)| i);
+    if split == 0 {
+        return format!("{}way", word);
+    }
+    // 'qu' moves as one sound, e.g. queen -> eenquay
+    if word[..split].ends_with(['q', 'Q']) && word[split..].starts_with(['u', 'U']) {
+        split += 1;
+    }
+
+    let (cluster, rest) = word.split_at(split);
+    let converted = format!("{}{}ay", rest, cluster);
+    if !word.starts_with(char::is_uppercase) {
+        return converted;
+    }
+    let mut chars = converted.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => converted,
+    }
+}
+
+#[derive(Debug)]
+pub enum Modifier {
+    Lowercase,
+    Uppercase,
+    NoSpaces,
+    DePunct,
+    Squeeze(bool),
+    Slugify,
+    Reverse,
+    Repeat(usize, bool),
+    Rot13,
+    Caesar(i32),
+    Atbash,
+    PigLatin,
+    Leet,
+    Unleet,
+    MorseEncode,
+    MorseDecode,
+    NumToWords,
+    Roman,
+    Unroman,
+    Indent(usize),
+    Dedent,
+    Pad(usize),
+    Center(usize),
+    Truncate(usize),
+    Tabify(usize),
+    Untabify(usize),
+    NumberLines,
+    Freq(bool),
+    VowelStats,
+    Xor(String),
+    XorDecode(String),
+    HexDump,
+    ExtractUrls,
+    ExtractEmails,
+    Csv,
+    CsvTranspose,
+    CsvHtml,
+    CsvGroupBy(usize, usize, Agg),
+    CsvDescribe,
+    Between(char, char),
+}
+
+// The modifiers Modifier::from_str accepts, for usage and error messages. Keep it in sync with
+// the parser below.
+pub const MODIFIERS_USAGE: &str =
+    "lowercase, uppercase, no-spaces, depunct, squeeze[:keep-lines], \
+    slugify, reverse, repeat:<count>[:nl], rot13, caesar:<shift>, caesar-decode:<shift>, atbash, \
+    piglatin, leet, unleet, morse-encode, morse-decode, num2words, roman, unroman, \
+    indent:<spaces>, dedent, pad:<width>, center:<width>, truncate:<characters>, tabify:<spaces>, \
+    untabify:<spaces>, number-lines, freq[:nows], vowels, xor:<key>, xor-decode:<key>, hexdump, \
+    extract-urls, extract-emails, csv, csv-transpose, csv-html, \
+    csv-groupby:<key column>:sum|count|mean:<column>, csv-describe, between:<open>:<close>";
+
+impl FromStr for Modifier {
+    type Err = OperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Parameterized modifiers carry their arguments after a colon, e.g. 'between:[:]'
+        let (name, args) = match s.split_once(':') {
+            Some((name, args)) => (name, Some(args)),
+            None => (s, None),
+        };
+
+        match (name.to_lowercase().as_str(), args) {
+            ("lowercase", None) => Ok(Modifier::Lowercase),
+            ("uppercase", None) => Ok(Modifier::Uppercase),
+            ("no-spaces", None) => Ok(Modifier::NoSpaces),
+            ("depunct", None) => Ok(Modifier::DePunct),
+            ("squeeze", None) => Ok(Modifier::Squeeze(false)),
+            ("squeeze", Some("keep-lines")) => Ok(Modifier::Squeeze(true)),
+            ("slugify", None) => Ok(Modifier::Slugify),
+            ("reverse", None) => Ok(Modifier::Reverse),
+            ("repeat", Some(args)) => {
+                let (count, newline) = match args.split_once(':') {
+                    Some((count, "nl")) => (count, true),
+                    _ => (args, false),
+                };
+                count
+                    .parse()
+                    .map(|count| Modifier::Repeat(count, newline))
+                    .map_err(|_| {
+                        OperationError(format!(
+                            "Invalid count '{}', use 'repeat:<count>' or 'repeat:<count>:nl'",
+                            args
+                        ))
+                    })
+            }
+            ("rot13", None) => Ok(Modifier::Rot13),
+            ("caesar" | "caesar-decode", Some(args)) => {
+                let shift: i32 = args.parse().map_err(|_| {
+                    OperationError(format!(
+                        "Invalid shift '{}', use '{}:<shift>'",
+                        args,
+                        name.to_lowercase()
+                    ))
+                })?;
+                // Decoding shifts the letters back by the same amount, reduced first so even
+                // i32::MIN can be negated
+                if name.eq_ignore_ascii_case("caesar-decode") {
+                    Ok(Modifier::Caesar(-shift.rem_euclid(26)))
+                } else {
+                    Ok(Modifier::Caesar(shift))
+                }
+            }
+            ("atbash", None) => Ok(Modifier::Atbash),
+            ("piglatin", None) => Ok(Modifier::PigLatin),
+            ("leet", None) => Ok(Modifier::Leet),
+            ("unleet", None) => Ok(Modifier::Unleet),
+            ("morse-encode", None) => Ok(Modifier::MorseEncode),
+            ("morse-decode", None) => Ok(Modifier::MorseDecode),
+            ("num2words", None) => Ok(Modifier::NumToWords),
+            ("roman", None) => Ok(Modifier::Roman),
+            ("unroman", None) => Ok(Modifier::Unroman),
+            ("indent", Some(args)) => args.parse().map(Modifier::Indent).map_err(|_| {
+                OperationError(format!("Invalid width '{}', use 'indent:<spaces>'", args))
+            }),
+            ("dedent", None) => Ok(Modifier::Dedent),
+            ("pad", Some(args)) => args.parse().map(Modifier::Pad).map_err(|_| {
+                OperationError(format!("Invalid width '{}', use 'pad:<width>'", args))
+            }),
+            ("center", Some(args)) => args.parse().map(Modifier::Center).map_err(|_| {
+                OperationError(format!("Invalid width '{}', use 'center:<width>'", args))
+            }),
+            ("truncate", Some(args)) => args.parse().map(Modifier::Truncate).map_err(|_| {
+                OperationError(format!(
+                    "Invalid length '{}', use 'truncate:<characters>'",
+                    args
+                ))
+            }),
+            ("tabify", Some(args)) => parse_tab_width("tabify", args).map(Modifier::Tabify),
+            ("untabify", Some(args)) => parse_tab_width("untabify", args).map(Modifier::Untabify),
+            ("number-lines", None) => Ok(Modifier::NumberLines),
+            ("freq", None) => Ok(Modifier::Freq(true)),
+            ("freq", Some("nows")) => Ok(Modifier::Freq(false)),
+            ("vowels", None) => Ok(Modifier::VowelStats),
+            ("xor", Some(key)) if !key.is_empty() => Ok(Modifier::Xor(key.to_string())),
+            ("xor-decode", Some(key)) if !key.is_empty() => {
+                Ok(Modifier::XorDecode(key.to_string()))
+            }
+            ("xor" | "xor-decode", _) => Err(OperationError(format!(
+                "Missing key, use '{}:<key>'",
+                name.to_lowercase()
+            ))),
+            ("hexdump", None) => Ok(Modifier::HexDump),
+            ("extract-urls", None) => Ok(Modifier::ExtractUrls),
+            ("extract-emails", None) => Ok(Modifier::ExtractEmails),
+            ("csv", None) => Ok(Modifier::Csv),
+            ("csv-transpose", None) => Ok(Modifier::CsvTranspose),
+            ("csv-html", None) => Ok(Modifier::CsvHtml),
+            ("csv-groupby", Some(args)) => parse_group_by(args),
+            ("csv-describe", None) => Ok(Modifier::CsvDescribe),
+            ("between", Some(args)) => {
+                let mut chars = args.chars();
+                match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                    (Some(open), Some(':'), Some(close), None) => {
+                        Ok(Modifier::Between(open, close))
+                    }
+                    _ => Err(OperationError(format!(
+                        "Invalid delimiters '{}', use 'between:<open>:<close>'",
+                        args
+                    ))),
+                }
+            }
+            _ => Err(OperationError(format!("Unknown modifier '{}'", s))),
+        }
+    }
+}
+
+// Parses '<key column>:<aggregation>:<column>' of 'csv-groupby', with columns counted from 1
+fn parse_group_by(args: &str) -> Result<Modifier, OperationError> {
+    let invalid = || {
+        OperationError(format!(
+            "Invalid grouping '{}', use 'csv-groupby:<key column>:sum|count|mean:<column>'",
+            args
+        ))
+    };
+    let column = |column: &str| match column.parse::<usize>() {
+        Ok(column) if column > 0 => Ok(column - 1),
+        _ => Err(invalid()),
+    };
+
+    let parts: Vec<&str> = args.split(':').collect();
+    match parts[..] {
+        [key, agg, agg_column] => Ok(Modifier::CsvGroupBy(
+            column(key)?,
+            column(agg_column)?,
+            agg.parse()?,
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+// Parses the tab width of 'tabify:<spaces>' and 'untabify:<spaces>', which must be at least 1
+fn parse_tab_width(name: &str, args: &str) -> Result<usize, OperationError> {
+    match args.parse() {
+        Ok(width) if width > 0 => Ok(width),
+        _ => Err(OperationError(format!(
+            "Invalid width '{}', use '{}:<spaces>' with at least 1 space",
+            args, name
+        ))),
+    }
+}
+
+pub struct TextModifier;
+
+impl TextModifier {
+    pub fn apply_lowercase(input: &str) -> String {
+        input.to_lowercase()
+    }
+
+    pub fn apply_uppercase(input: &str) -> String {
+        input.to_uppercase()
+    }
+
+    pub fn remove_spaces(input: &str) -> String {
+        input.replace(' ', "")
+    }
+
+    // Removes Unicode punctuation such as commas, brackets, dashes or '¿', keeping letters,
+    // digits, symbols and whitespace
+    pub fn apply_depunct(input: &str) -> String {
+        input.chars().filter(|c| !c.is_punctuation()).collect()
+    }
+
+    // Collapses runs of whitespace into a single space and trims the ends. With keep_lines, each
+    // line is squeezed on its own and the line breaks are kept.
+    pub fn apply_squeeze(input: &str, keep_lines: bool) -> String {
+        let squeeze = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if keep_lines {
+            input.lines().map(squeeze).collect::<Vec<_>>().join("\n")
+        } else {
+            squeeze(input)
+        }
+    }
+
+    pub fn apply_slugify(input: &str) -> String {
+        slugify(input)
+    }
+
+    pub fn apply_reverse(input: &str) -> String {
+        input.chars().rev().collect()
+    }
+
+    // Concatenates the input count times, joined by newlines if newline is set
+    pub fn repeat(input: &str, count: usize, newline: bool) -> Result<String, OperationError> {
+        if count > MAX_REPEAT {
+            return Err(OperationError(format!(
+                "Can't repeat {} times, at most {} is allowed",
+                count, MAX_REPEAT
+            )));
+        }
+        let separator = if newline { "\n" } else { "" };
+        Ok(vec![input; count].join(separator))
+    }
+
+    pub fn apply_rot13(input: &str) -> String {
+        TextModifier::apply_rotn(input, 13)
+    }
+
+    // Shifts each ASCII letter by shift places in the alphabet, wrapping around and keeping its
+    // case. Negative shifts go backwards, so shifting by -n undoes a shift by n.
+    pub fn apply_rotn(input: &str, shift: i32) -> String {
+        let shift = shift.rem_euclid(26) as u8;
+        input
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+                    (((c as u8 - base + shift) % 26) + base) as char
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    // Converts every word to pig latin, leaving punctuation and whitespace around the words in place
+    pub fn apply_piglatin(input: &str) -> String {
+        let mut output = String::with_capacity(input.len() + input.len() / 2);
+        let mut word = String::new();
+        for c in input.chars() {
+            if c.is_alphabetic() {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                output.push_str(&pig_latin_word(&word));
+                word.clear();
+            }
+            output.push(c);
+        }
+        if !word.is_empty() {
+            output.push_str(&pig_latin_word(&word));
+        }
+        output
+    }
+
+    // Mirrors each ASCII letter within the alphabet, a <-> z, b <-> y, keeping its case
+    pub fn apply_atbash(input: &str) -> String {
+        input
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+                    (base + 25 - (c as u8 - base)) as char
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    // Replaces the letters a, e, i, o, t and s with look-alike digits, in either case
+    pub fn apply_leet(input: &str) -> String {
+        input
+            .chars()
+            .map(|c| match c.to_ascii_lowercase() {
+                'a' => '4',
+                'e' => '3',
+                'i' => '1',
+                'o' => '0',
+                't' => '7',
+                's' => '5',
+                _ => c,
+            })
+            .collect()
+    }
+
+    // Best-effort reverse of leet: the original case is lost, so digits turn into lowercase letters
+    pub fn apply_unleet(input: &str) -> String {
+        input
+            .chars()
+            .map(|c| match c {
+                '4' => 'a',
+                '3' => 'e',
+                '1' => 'i',
+                '0' => 'o',
+                '7' => 't',
+                '5' => 's',
+                _ => c,
+            })
+            .collect()
+    }
+
+    // Encodes letters and digits as dot-dash sequences separated by spaces, and words by " / "
+    pub fn morse_encode(input: &str) -> Result<String, OperationError> {
+        let words = input
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .map(|c| {
+                        MORSE_CODE
+                            .iter()
+                            .find(|(letter, _)| *letter == c.to_ascii_uppercase())
+                            .map(|(_, code)| *code)
+                            .ok_or_else(|| OperationError(format!("No Morse code for '{}'", c)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|codes| codes.join(" "))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(words.join(" / "))
+    }
+
+    // Decodes the output of morse_encode into uppercase text
+    pub fn morse_decode(input: &str) -> Result<String, OperationError> {
+        let words = input
+            .split('/')
+            .map(|word| {
+                word.split_whitespace()
+                    .map(|token| {
+                        MORSE_CODE
+                            .iter()
+                            .find(|(_, code)| *code == token)
+                            .map(|(letter, _)| *letter)
+                            .ok_or_else(|| {
+                                OperationError(format!("Unknown Morse code '{}'", token))
+                            })
+                    })
+                    .collect::<Result<String, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(words.join(" "))
+    }
+
+    // Replaces each standalone integer, e.g. "42" or "-7", with its English words and keeps
+    // the rest of the text and its whitespace as it is
+    pub fn num_to_words(input: &str) -> Result<String, OperationError> {
+        let mut output = String::with_capacity(input.len());
+        for piece in input.split_inclusive(char::is_whitespace) {
+            let token = piece.trim_end_matches(char::is_whitespace);
+            let (sign, digits) = match token.strip_prefix('-') {
+                Some(digits) => ("minus ", digits),
+                None => ("", token),
+            };
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                output.push_str(piece);
+                continue;
+            }
+
+            let number = digits
+                .parse::<u64>()
+                .ok()
+                .filter(|&n| n <= MAX_NUM_WORDS)
+                .ok_or_else(|| {
+                    OperationError(format!(
+                        "{} is out of range, at most {} is supported",
+                        token, MAX_NUM_WORDS
+                    ))
+                })?;
+            let sign = if number == 0 { "" } else { sign };
+            output.push_str(sign);
+            output.push_str(&number_to_words(number));
+            output.push_str(&piece[token.len()..]);
+        }
+        Ok(output)
+    }
+
+    // Converts the whole trimmed input, an integer from 1 to 3999, to a Roman numeral
+    pub fn to_roman(input: &str) -> Result<String, OperationError> {
+        let mut number = input
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..=3999).contains(n))
+            .ok_or_else(|| {
+                OperationError(format!(
+                    "'{}' is not an integer from 1 to 3999",
+                    input.trim()
+                ))
+            })?;
+
+        let mut numeral = String::new();
+        for &(value, symbol) in &ROMAN_NUMERALS {
+            while number >= value {
+                numeral.push_str(symbol);
+                number -= value;
+            }
+        }
+        Ok(numeral)
+    }
+
+    // Converts the whole trimmed input, a Roman numeral in its standard form, to an integer
+    pub fn from_roman(input: &str) -> Result<String, OperationError> {
+        let numeral = input.trim().to_uppercase();
+        let mut rest = numeral.as_str();
+        let mut number = 0;
+        for &(value, symbol) in &ROMAN_NUMERALS {
+            while let Some(tail) = rest.strip_prefix(symbol) {
+                number += value;
+                rest = tail;
+            }
+        }
+
+        // Re-encoding rejects leftovers and non-standard forms such as "IIII" or "VX"
+        let invalid = || OperationError(format!("'{}' is not a valid Roman numeral", input.trim()));
+        if !rest.is_empty() || number == 0 {
+            return Err(invalid());
+        }
+        match Self::to_roman(&number.to_string()) {
+            Ok(canonical) if canonical == numeral => Ok(number.to_string()),
+            _ => Err(invalid()),
+        }
+    }
+
+    // Pads the input with spaces on the right up to width terminal columns, so wide characters
+    // such as CJK count twice. Longer input is returned unchanged.
+    pub fn pad(input: &str, width: usize) -> String {
+        let padding = width.saturating_sub(input.width());
+        format!("{}{}", input, " ".repeat(padding))
+    }
+
+    // Centers the input within width terminal columns, putting the odd space on the right.
+    // Longer input is returned unchanged.
+    pub fn center(input: &str, width: usize) -> String {
+        let padding = width.saturating_sub(input.width());
+        let left = padding / 2;
+        format!(
+            "{}{}{}",
+            " ".repeat(left),
+            input,
+            " ".repeat(padding - left)
+        )
+    }
+
+    // Cuts the input to max_chars characters and marks the cut with an ellipsis. Input of at most
+    // max_chars characters is returned unchanged.
+    pub fn truncate(input: &str, max_chars: usize) -> String {
+        match input.char_indices().nth(max_chars) {
+            Some((end, _)) => format!("{}…", &input[..end]),
+            None => input.to_string(),
+        }
+    }
+
+    // Prepends the given number of spaces to every line, leaving blank lines empty
+    pub fn indent(input: &str, width: usize) -> String {
+        let padding = " ".repeat(width);
+        input
+            .split('\n')
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.to_string()
+                } else {
+                    format!("{}{}", padding, line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Removes the leading whitespace common to all non-blank lines
+    pub fn dedent(input: &str) -> String {
+        let common = input
+            .split('\n')
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .reduce(|common, prefix| {
+                let len = common
+                    .chars()
+                    .zip(prefix.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a.len_utf8())
+                    .sum();
+                &common[..len]
+            })
+            .unwrap_or("");
+
+        input
+            .split('\n')
+            .map(|line| line.strip_prefix(common).unwrap_or(line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Replaces each tab with the given number of spaces
+    pub fn untabify(input: &str, width: usize) -> String {
+        input.replace('\t', &" ".repeat(width))
+    }
+
+    // Replaces each run of the given number of leading spaces with a tab, line by line. Leftover
+    // leading spaces and spaces after the first other character are kept.
+    pub fn tabify(input: &str, width: usize) -> String {
+        input
+            .split('\n')
+            .map(|line| {
+                let body = line.trim_start_matches(' ');
+                let spaces = line.len() - body.len();
+                format!(
+                    "{}{}{}",
+                    "\t".repeat(spaces / width),
+                    " ".repeat(spaces % width),
+                    body
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Prefixes each line with its 1-based number, right-aligned to the width of the last one.
+    // A trailing newline ends the last line rather than starting an empty numbered one.
+    pub fn apply_number_lines(input: &str) -> String {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.len().to_string().len();
+        let mut numbered = lines
+            .iter()
+            .enumerate()
+            .map(|(e, line)| format!("{:>width$} | {}", e + 1, line, width = width))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if input.ends_with('\n') {
+            numbered.push('\n');
+        }
+        numbered
+    }
+
+    // Sorts the characters into ASCII vowels (a, e, i, o, u in any case), other ASCII letters,
+    // ASCII digits and everything else, including whitespace and non-ASCII letters
+    pub fn vowel_stats(input: &str) -> String {
+        let (mut vowels, mut consonants, mut digits, mut other) = (0, 0, 0, 0);
+        for c in input.chars() {
+            match c.to_ascii_lowercase() {
+                'a' | 'e' | 'i' | 'o' | 'u' => vowels += 1,
+                'a'..='z' => consonants += 1,
+                '0'..='9' => digits += 1,
+                _ => other += 1,
+            }
+        }
+        format!(
+            "vowels: {}, consonants: {}, digits: {}, other: {}",
+            vowels, consonants, digits, other
+        )
+    }
+
+    // Counts the occurrences of each character, one 'char: count' line per character sorted by
+    // descending count and then by codepoint. Whitespace is escaped, e.g. '\n', or skipped.
+    pub fn char_frequencies(input: &str, include_whitespace: bool) -> String {
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for c in input
+            .chars()
+            .filter(|c| include_whitespace || !c.is_whitespace())
+        {
+            *counts.entry(c).or_default() += 1;
+        }
+
+        let mut counts: Vec<(char, usize)> = counts.into_iter().collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        counts
+            .iter()
+            .map(|(c, count)| format!("{}: {}", c.escape_debug(), count))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // XORs the bytes of the input against the repeating key and returns them as lowercase hex,
+    // so the result stays printable. This is obfuscation, not encryption.
+    pub fn xor_encode(input: &str, key: &str) -> String {
+        input
+            .bytes()
+            .zip(key.bytes().cycle())
+            .map(|(byte, k)| format!("{:02x}", byte ^ k))
+            .collect()
+    }
+
+    // Reverses xor_encode: parses the hex input and XORs it against the same key
+    pub fn xor_decode(input: &str, key: &str) -> Result<String, OperationError> {
+        let hex = input.trim();
+        let invalid = || OperationError(format!("Invalid hex input '{}'", hex));
+        if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .zip(key.bytes().cycle())
+            .map(|(i, k)| {
+                u8::from_str_radix(&hex[i..i + 2], 16)
+                    .map(|byte| byte ^ k)
+                    .map_err(|_| invalid())
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        String::from_utf8(bytes)
+            .map_err(|_| OperationError("Decoded text isn't valid UTF-8, wrong key?".to_string()))
+    }
+
+    // Renders the input bytes like 'hexdump -C': the offset, 16 bytes in hex split into two groups
+    // of 8, and the same bytes as ASCII with non-printable ones shown as '.'
+    pub fn apply_hexdump(input: &str) -> String {
+        input
+            .as_bytes()
+            .chunks(16)
+            .enumerate()
+            .map(|(row, bytes)| {
+                let mut hex = String::new();
+                for (e, byte) in bytes.iter().enumerate() {
+                    if e == 8 {
+                        hex.push(' ');
+                    }
+                    hex.push_str(&format!("{:02x} ", byte));
+                }
+                let ascii: String = bytes
+                    .iter()
+                    .map(|&byte| {
+                        if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect();
+                format!("{:08x}  {:<49} |{}|", row * 16, hex, ascii)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Returns the http and https URLs in the input, one per line. Punctuation ending a sentence
+    // right after a URL isn't taken as part of it.
+    pub fn extract_urls(input: &str) -> String {
+        static URL: OnceLock<Regex> = OnceLock::new();
+        let url = URL.get_or_init(|| Regex::new(r#"https?://[^\s<>"']+"#).unwrap());
+        url.find_iter(input)
+            .map(|found| {
+                found
+                    .as_str()
+                    .trim_end_matches(['.', ',', ';', ':', '!', '?', ')'])
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Returns the email addresses in the input, one per line
+    pub fn extract_emails(input: &str) -> String {
+        static EMAIL: OnceLock<Regex> = OnceLock::new();
+        let email = EMAIL.get_or_init(|| {
+            Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap()
+        });
+        email
+            .find_iter(input)
+            .map(|found| found.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Returns the text between each pair of delimiters, one match per line.
+    // A trailing unmatched open delimiter is ignored.
+    pub fn between(input: &str, open: char, close: char) -> String {
+        let mut matches = Vec::new();
+        let mut current: Option<String> = None;
+
+        for c in input.chars() {
+            match current.as_mut() {
+                Some(_) if c == close => matches.extend(current.take()),
+                Some(text) => text.push(c),
+                None if c == open => current = Some(String::new()),
+                None => {}
+            }
+        }
+
+        matches.join("\n")
+    }
+
+    pub fn parse_csv(input: &str) -> Result<Csv, Box<dyn Error>> {
+        Self::parse_csv_with_delimiter(input, b';')
+    }
+
+    // Same as parse_csv, for CSV separated by another delimiter than ';', e.g. b','
+    pub fn parse_csv_with_delimiter(input: &str, delimiter: u8) -> Result<Csv, Box<dyn Error>> {
+        let mut reader = ReaderBuilder::new()
+            .has_headers(false) // default value is true and then we miss the first row (headers)
+            .delimiter(delimiter)
+            .from_reader(input.as_bytes());
+        let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+
+        let headers: Vec<String> = records
+            .first()
+            .ok_or_else(|| "CSV must have at least one row".to_string())?
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+
+        let rows: Vec<Vec<String>> = records[1..]
+            .iter()
+            .map(|record| record.iter().map(|field| field.to_string()).collect())
+            .collect();
+
+        Ok(Csv { headers, rows })
+    }
+}
+
+pub fn execute_operation(modifier: Modifier, text: &str) -> Result<String, Box<dyn Error>> {
+    match modifier {
+        Modifier::Lowercase => Ok(TextModifier::apply_lowercase(text)),
+        Modifier::Uppercase => Ok(TextModifier::apply_uppercase(text)),
+        Modifier::NoSpaces => Ok(TextModifier::remove_spaces(text)),
+        Modifier::DePunct => Ok(TextModifier::apply_depunct(text)),
+        Modifier::Squeeze(keep_lines) => Ok(TextModifier::apply_squeeze(text, keep_lines)),
+        Modifier::Slugify => Ok(TextModifier::apply_slugify(text)),
+        Modifier::Reverse => Ok(TextModifier::apply_reverse(text)),
+        Modifier::Repeat(count, newline) => Ok(TextModifier::repeat(text, count, newline)?),
+        Modifier::Rot13 => Ok(TextModifier::apply_rot13(text)),
+        Modifier::Caesar(shift) => Ok(TextModifier::apply_rotn(text, shift)),
+        Modifier::Atbash => Ok(TextModifier::apply_atbash(text)),
+        Modifier::PigLatin => Ok(TextModifier::apply_piglatin(text)),
+        Modifier::Leet => Ok(TextModifier::apply_leet(text)),
+        Modifier::Unleet => Ok(TextModifier::apply_unleet(text)),
+        Modifier::MorseEncode => Ok(TextModifier::morse_encode(text)?),
+        Modifier::MorseDecode => Ok(TextModifier::morse_decode(text)?),
+        Modifier::NumToWords => Ok(TextModifier::num_to_words(text)?),
+        Modifier::Roman => Ok(TextModifier::to_roman(text)?),
+        Modifier::Unroman => Ok(TextModifier::from_roman(text)?),
+        Modifier::Indent(width) => Ok(TextModifier::indent(text, width)),
+        Modifier::Dedent => Ok(TextModifier::dedent(text)),
+        Modifier::Pad(width) => Ok(TextModifier::pad(text, width)),
+        Modifier::Center(width) => Ok(TextModifier::center(text, width)),
+        Modifier::Truncate(max_chars) => Ok(TextModifier::truncate(text, max_chars)),
+        Modifier::Tabify(width) => Ok(TextModifier::tabify(text, width)),
+        Modifier::Untabify(width) => Ok(TextModifier::untabify(text, width)),
+        Modifier::NumberLines => Ok(TextModifier::apply_number_lines(text)),
+        Modifier::Xor(key) => Ok(TextModifier::xor_encode(text, &key)),
+        Modifier::XorDecode(key) => Ok(TextModifier::xor_decode(text, &key)?),
+        Modifier::HexDump => Ok(TextModifier::apply_hexdump(text)),
+        Modifier::VowelStats => Ok(TextModifier::vowel_stats(text)),
+        Modifier::Freq(include_whitespace) => {
+            Ok(TextModifier::char_frequencies(text, include_whitespace))
+        }
+        Modifier::ExtractUrls => Ok(TextModifier::extract_urls(text)),
+        Modifier::ExtractEmails => Ok(TextModifier::extract_emails(text)),
+        Modifier::Csv => Ok(format!(
+            "\nCSV output: \n\n{}",
+            TextModifier::parse_csv(text)?
+        )),
+        Modifier::CsvHtml => Ok(TextModifier::parse_csv(text)?.to_html()),
+        Modifier::CsvDescribe => Ok(format!(
+            "\nCSV output: \n\n{}",
+            TextModifier::parse_csv(text)?.describe()
+        )),
+        Modifier::CsvGroupBy(key_col, agg_col, agg) => Ok(format!(
+            "\nCSV output: \n\n{}",
+            TextModifier::parse_csv(text)?.group_by(key_col, agg_col, agg)?
+        )),
+        Modifier::CsvTranspose => Ok(format!(
+            "\nCSV output: \n\n{}",
+            TextModifier::parse_csv(text)?.transpose()
+        )),
+        Modifier::Between(open, close) => Ok(TextModifier::between(text, open, close)),
+    }
+}
+
+// Applies a single modifier to the input, the entry point for using the modifiers from other code.
+// Errors of the modifier, e.g. malformed CSV, are reported as an OperationError.
+pub fn transform(modifier: Modifier, input: &str) -> Result<String, OperationError> {
+    execute_operation(modifier, input).map_err(into_operation_error)
+}
+
+// Returns an OperationError as it is and wraps errors of other types, so messages aren't
+// prefixed with 'Operation Error: ' twice
+fn into_operation_error(err: Box<dyn Error>) -> OperationError {
+    match err.downcast::<OperationError>() {
+        Ok(err) => *err,
+        Err(err) => OperationError(err.to_string()),
+    }
+}
+
+// Runs the text through every modifier of the pipeline, feeding each output into the next stage.
+// Each stage comes with the modifier as the user typed it, which names the stage in errors.
+pub fn execute_pipeline(
+    stages: Vec<(&str, Modifier)>,
+    text: &str,
+) -> Result<String, Box<dyn Error>> {
+    stages
+        .into_iter()
+        .enumerate()
+        .try_fold(text.to_string(), |acc, (stage, (name, modifier))| {
+            execute_operation(modifier, &acc).map_err(|err| {
+                let OperationError(message) = into_operation_error(err);
+                OperationError(format!(
+                    "Stage {} ({}) failed: {}",
+                    stage + 1,
+                    name,
+                    message
+                ))
+                .into()
+            })
+        })
+}
+
+// Splits '<modifier> | <modifier> ... <text>' into the modifier names and the text part.
+// Only a '|' with whitespace on both sides separates stages, so 'between:|:|' or an unquoted word
+// such as 'a|b' keep their pipes. Quoted text isn't searched at all.
+pub fn split_pipeline(input: &str) -> Option<(Vec<&str>, &str)> {
+    let head_end = input.find('\'').unwrap_or(input.len());
+
+    let mut modifiers = Vec::new();
+    let mut stage_start = 0;
+    for (pipe, _) in input[..head_end].match_indices('|') {
+        let before = input[..pipe].chars().next_back();
+        let after = input[pipe + 1..].chars().next();
+        if before.is_some_and(char::is_whitespace) && after.is_some_and(char::is_whitespace) {
+            modifiers.push(input[stage_start..pipe].trim());
+            stage_start = pipe + 1;
+        }
+    }
+
+    // The part after the last separator still holds '<modifier> <text>'
+    let (modifier, text) = input[stage_start..].trim_start().split_once(' ')?;
+    modifiers.push(modifier);
+
+    Some((modifiers, text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_three_stage_pipeline() {
+        let (modifier_strs, text) =
+            split_pipeline("lowercase | reverse | rot13 'Hello World'").unwrap();
+        assert_eq!(modifier_strs, vec!["lowercase", "reverse", "rot13"]);
+        assert_eq!(text, "'Hello World'");
+
+        let stages = modifier_strs
+            .iter()
+            .map(|modifier_str| (*modifier_str, modifier_str.parse::<Modifier>().unwrap()))
+            .collect();
+        // "Hello World" -> "hello world" -> "dlrow olleh" -> "qyebj byyru"
+        assert_eq!(
+            execute_pipeline(stages, "Hello World").unwrap(),
+            "qyebj byyru"
+        );
+    }
+
+    #[test]
+    fn test_split_pipeline_keeps_pipes_in_words() {
+        let (modifier_strs, text) = split_pipeline("rot13 a|b").unwrap();
+        assert_eq!(modifier_strs, vec!["rot13"]);
+        assert_eq!(text, "a|b");
+        let modifier = modifier_strs[0].parse::<Modifier>().unwrap();
+        assert_eq!(execute_operation(modifier, text).unwrap(), "n|o");
+
+        let (modifier_strs, text) = split_pipeline("uppercase | between:|:| 'a|b|c'").unwrap();
+        assert_eq!(modifier_strs, vec!["uppercase", "between:|:|"]);
+        assert_eq!(text, "'a|b|c'");
+    }
+
+    #[test]
+    fn test_pipeline_names_failing_stage() {
+        let stages = vec![("uppercase", Modifier::Uppercase), ("csv", Modifier::Csv)];
+        let err = execute_pipeline(stages, "").unwrap_err();
+        assert!(err.to_string().contains("Stage 2 (csv)"));
+
+        // The stage is named as typed, and the error of the modifier isn't prefixed again
+        let stages = vec![("xor-decode:k", "xor-decode:k".parse().unwrap())];
+        let err = execute_pipeline(stages, "zz").unwrap_err().to_string();
+        assert!(err.starts_with("Operation Error: Stage 1 (xor-decode:k) failed: "));
+        assert_eq!(err.matches("Operation Error: ").count(), 1);
+    }
+
+    #[test]
+    fn test_between_multiple_matches() {
+        let modifier = "between:[:]".parse::<Modifier>().unwrap();
+        assert_eq!(execute_operation(modifier, "a[b]c[d]").unwrap(), "b\nd");
+    }
+
+    #[test]
+    fn test_squeeze_collapses_whitespace() {
+        assert_eq!(TextModifier::apply_squeeze("a   b\t c", false), "a b c");
+        assert_eq!(
+            TextModifier::apply_squeeze("  a \n\n  b  c ", false),
+            "a b c"
+        );
+
+        let modifier = "squeeze:keep-lines".parse::<Modifier>().unwrap();
+        assert_eq!(
+            execute_operation(modifier, "  a \n\n  b  c ").unwrap(),
+            "a\n\nb c"
+        );
+    }
+
+    #[test]
+    fn test_repeat() {
+        let modifier = "repeat:3".parse::<Modifier>().unwrap();
+        assert_eq!(execute_operation(modifier, "ab").unwrap(), "ababab");
+        let modifier = "repeat:3:nl".parse::<Modifier>().unwrap();
+        assert_eq!(execute_operation(modifier, "ab").unwrap(), "ab\nab\nab");
+        assert_eq!(TextModifier::repeat("ab", 0, false).unwrap(), "");
+
+        assert!(TextModifier::repeat("ab", MAX_REPEAT + 1, false).is_err());
+        assert!("repeat:3:tab".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_extract_urls_and_emails() {
+        let text = "Docs are at https://doc.rust-lang.org/book/, the crate at \
+                    (http://crates.io/crates/regex). Mail jan.vais@example.com.";
+        assert_eq!(
+            TextModifier::extract_urls(text),
+            "https://doc.rust-lang.org/book/\nhttp://crates.io/crates/regex"
+        );
+        assert_eq!(TextModifier::extract_emails(text), "jan.vais@example.com");
+
+        // No matches are no error
+        let modifier = "extract-emails".parse::<Modifier>().unwrap();
+        assert_eq!(execute_operation(modifier, "nothing here").unwrap(), "");
+    }
+
+    #[test]
+    fn test_leet_round_trip() {
+        assert_eq!(TextModifier::apply_leet("leet speak"), "l337 5p34k");
+        assert_eq!(TextModifier::apply_leet("LEET Speak!"), "L337 5p34k!");
+        assert_eq!(TextModifier::apply_unleet("l337 5p34k"), "leet speak");
+    }
+
+    #[test]
+    fn test_morse_round_trip() {
+        let encoded = TextModifier::morse_encode("SOS HELP").unwrap();
+        assert_eq!(encoded, "... --- ... / .... . .-.. .--.");
+        assert_eq!(TextModifier::morse_decode(&encoded).unwrap(), "SOS HELP");
+    }
+
+    #[test]
+    fn test_morse_reports_unknown_symbols() {
+        let err = TextModifier::morse_encode("SOS!").unwrap_err();
+        assert_eq!(err.0, "No Morse code for '!'");
+        let err = TextModifier::morse_decode("... ---- ...").unwrap_err();
+        assert_eq!(err.0, "Unknown Morse code '----'");
+    }
+
+    #[test]
+    fn test_num_to_words() {
+        assert_eq!(TextModifier::num_to_words("0").unwrap(), "zero");
+        assert_eq!(
+            TextModifier::num_to_words("I have 42 cats and 7 dogs").unwrap(),
+            "I have forty-two cats and seven dogs"
+        );
+        assert_eq!(
+            TextModifier::num_to_words("123456789").unwrap(),
+            "one hundred twenty-three million four hundred fifty-six thousand seven hundred eighty-nine"
+        );
+        assert_eq!(
+            TextModifier::num_to_words("-1000 x42\n").unwrap(),
+            "minus one thousand x42\n"
+        );
+        assert!(TextModifier::num_to_words("1000000000").is_err());
+    }
+
+    #[test]
+    fn test_roman_round_trip() {
+        for (number, numeral) in [("4", "IV"), ("9", "IX"), ("1994", "MCMXCIV")] {
+            assert_eq!(TextModifier::to_roman(number).unwrap(), numeral);
+            assert_eq!(TextModifier::from_roman(numeral).unwrap(), number);
+        }
+    }
+
+    #[test]
+    fn test_roman_rejects_invalid_input() {
+        assert!(TextModifier::to_roman("0").is_err());
+        assert!(TextModifier::to_roman("4000").is_err());
+        assert!(TextModifier::from_roman("IIII").is_err());
+        assert!(TextModifier::from_roman("IC").is_err());
+        assert!(TextModifier::from_roman("XLZ").is_err());
+        assert!(TextModifier::from_roman("").is_err());
+    }
+
+    #[test]
+    fn test_indent_keeps_blank_lines() {
+        let modifier = "indent:4".parse::<Modifier>().unwrap();
+        assert_eq!(
+            execute_operation(modifier, "fn main() {\n\n}").unwrap(),
+            "    fn main() {\n\n    }"
+        );
+        assert!("indent:four".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_dedent_removes_common_indentation() {
+        assert_eq!(
+            TextModifier::dedent("        if x {\n\n            y();\n        }"),
+            "if x {\n\n    y();\n}"
+        );
+    }
+
+    #[test]
+    fn test_tabify_and_untabify() {
+        let tabify = "tabify:4".parse::<Modifier>().unwrap();
+        assert_eq!(
+            execute_operation(tabify, "if x {\n        y(a,    b);\n      z();\n}").unwrap(),
+            "if x {\n\t\ty(a,    b);\n\t  z();\n}"
+        );
+
+        let untabify = "untabify:4".parse::<Modifier>().unwrap();
+        assert_eq!(
+            execute_operation(untabify, "if x {\n\t\ty();\n}").unwrap(),
+            "if x {\n        y();\n}"
+        );
+        assert!("tabify:0".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_number_lines_aligns_numbers() {
+        let input: String = ('a'..='j').map(|c| format!("{}\n", c)).collect();
+        let numbered = TextModifier::apply_number_lines(&input);
+        let lines: Vec<&str> = numbered.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], " 1 | a");
+        assert_eq!(lines[8], " 9 | i");
+        assert_eq!(lines[9], "10 | j");
+        assert!(numbered.ends_with("j\n"));
+        assert_eq!(TextModifier::apply_number_lines("a\nb"), "1 | a\n2 | b");
+    }
+
+    #[test]
+    fn test_freq_sorts_by_count_then_char() {
+        assert_eq!(
+            TextModifier::char_frequencies("hello world", true),
+            "l: 3\no: 2\n : 1\nd: 1\ne: 1\nh: 1\nr: 1\nw: 1"
+        );
+        let modifier = "freq:nows".parse::<Modifier>().unwrap();
+        assert_eq!(execute_operation(modifier, "a b\nb").unwrap(), "b: 2\na: 1");
+    }
+
+    #[test]
+    fn test_xor_round_trip() {
+        let encoded = TextModifier::xor_encode("Hello, World!", "key");
+        assert_eq!(encoded, "230015070a554b321619091d4a");
+        assert_eq!(
+            TextModifier::xor_decode(&encoded, "key").unwrap(),
+            "Hello, World!"
+        );
+        assert!(TextModifier::xor_decode("abc", "key").is_err());
+        assert!("xor:".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_hexdump_spans_rows() {
+        let dump = TextModifier::apply_hexdump("Hello, World!\n\tbytes");
+        assert_eq!(
+            dump,
+            "00000000  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 0a 09 62  |Hello, World!..b|\n\
+             00000010  79 74 65 73                                       |ytes|"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_rot13_round_trips(text in "\\PC*") {
+            let rotated = TextModifier::apply_rot13(&text);
+            prop_assert_eq!(TextModifier::apply_rot13(&rotated), text);
+        }
+
+        #[test]
+        fn prop_rot13_keeps_non_letters(text in "\\PC*") {
+            for (original, rotated) in text.chars().zip(TextModifier::apply_rot13(&text).chars()) {
+                if original.is_ascii_alphabetic() {
+                    prop_assert_ne!(original, rotated);
+                    prop_assert_eq!(original.is_ascii_lowercase(), rotated.is_ascii_lowercase());
+                } else {
+                    prop_assert_eq!(original, rotated);
+                }
+            }
+        }
+
+        #[test]
+        fn prop_atbash_round_trips(text in "\\PC*") {
+            let mirrored = TextModifier::apply_atbash(&text);
+            prop_assert_eq!(TextModifier::apply_atbash(&mirrored), text);
+        }
+
+        #[test]
+        fn prop_reverse_round_trips(text in any::<String>()) {
+            let reversed = TextModifier::apply_reverse(&text);
+            prop_assert_eq!(TextModifier::apply_reverse(&reversed), text);
+        }
+    }
+
+    #[test]
+    fn test_caesar_shift_3_round_trips() {
+        let encode = "caesar:3".parse::<Modifier>().unwrap();
+        let decode = "caesar-decode:3".parse::<Modifier>().unwrap();
+
+        let encoded = execute_operation(encode, "Hello, xyz!").unwrap();
+        assert_eq!(encoded, "Khoor, abc!");
+        assert_eq!(execute_operation(decode, &encoded).unwrap(), "Hello, xyz!");
+        assert!("caesar:three".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_pad_uses_display_width() {
+        assert_eq!(TextModifier::pad("abc", 6), "abc   ");
+        // Each of the CJK characters takes two columns
+        assert_eq!(TextModifier::pad("東京", 6), "東京  ");
+        assert_eq!(TextModifier::pad("Žofie", 6), "Žofie ");
+        assert_eq!(TextModifier::pad("too long", 3), "too long");
+    }
+
+    #[test]
+    fn test_center_puts_odd_space_right() {
+        assert_eq!(TextModifier::center("abc", 8), "  abc   ");
+        assert_eq!(TextModifier::center("東京", 8), "  東京  ");
+        assert_eq!(TextModifier::center("too long", 3), "too long");
+        assert!("center:wide".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_truncate_counts_characters() {
+        assert_eq!(TextModifier::truncate("Žluťoučký", 20), "Žluťoučký");
+        assert_eq!(TextModifier::truncate("Žluťoučký", 9), "Žluťoučký");
+        assert_eq!(TextModifier::truncate("Žluťoučký kůň", 9), "Žluťoučký…");
+        assert_eq!(TextModifier::truncate("東京タワー", 2), "東京…");
+        assert_eq!(TextModifier::truncate("abc", 0), "…");
+    }
+
+    #[test]
+    fn test_piglatin_words() {
+        assert_eq!(TextModifier::apply_piglatin("hello"), "ellohay");
+        assert_eq!(TextModifier::apply_piglatin("apple"), "appleway");
+        assert_eq!(
+            TextModifier::apply_piglatin("string queen"),
+            "ingstray eenquay"
+        );
+        assert_eq!(
+            TextModifier::apply_piglatin("Hello, world! (rhythm)"),
+            "Ellohay, orldway! (ythmrhay)"
+        );
+    }
+
+    #[test]
+    fn test_depunct_keeps_words() {
+        assert_eq!(
+            TextModifier::apply_depunct("Hello, world! (foo)"),
+            "Hello world foo"
+        );
+        assert_eq!(
+            TextModifier::apply_depunct("¿Qué tal? «Bien» – 100 %"),
+            "Qué tal Bien  100 "
+        );
+    }
+
+    #[test]
+    fn test_vowel_stats_buckets() {
+        assert_eq!(
+            TextModifier::vowel_stats("Hello World 2024, Žofie!"),
+            "vowels: 6, consonants: 8, digits: 4, other: 6"
+        );
+        assert_eq!(
+            TextModifier::vowel_stats(""),
+            "vowels: 0, consonants: 0, digits: 0, other: 0"
+        );
+    }
+
+    #[test]
+    fn test_transform_applies_modifier() {
+        assert_eq!(transform(Modifier::Uppercase, "hello").unwrap(), "HELLO");
+        assert_eq!(transform(Modifier::Rot13, "Hello").unwrap(), "Uryyb");
+        assert_eq!(
+            transform(Modifier::Atbash, "Hello, Zoe!").unwrap(),
+            "Svool, Alv!"
+        );
+        assert_eq!(
+            transform(Modifier::Slugify, "Hello World!").unwrap(),
+            "hello-world"
+        );
+    }
+
+    #[test]
+    fn test_transform_reports_operation_error() {
+        let err = transform(Modifier::Csv, "").unwrap_err();
+        assert!(err.to_string().starts_with("Operation Error: "));
+
+        // Errors of the modifiers themselves aren't wrapped a second time
+        let err = transform(Modifier::Roman, "0").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Operation Error: '0' is not an integer from 1 to 3999"
+        );
+    }
+
+    #[test]
+    fn test_csv_aligns_wide_characters() {
+        let csv = TextModifier::parse_csv("name;city\nŽofie;東京\nBob;Brno").unwrap();
+        let table = csv.to_string();
+
+        // Every line ends at the same terminal column, although the fields differ in bytes
+        let widths: Vec<usize> = table.lines().map(|line| line.trim_end().width()).collect();
+        assert!(widths.iter().all(|&width| width == widths[0]), "{}", table);
+        assert!(table.contains("| 東京 |"));
+        assert!(table.contains("| Brno |"));
+    }
+
+    #[test]
+    fn test_csv_right_aligns_numeric_columns() {
+        let csv =
+            TextModifier::parse_csv("item;price;note\ntea;4.5;green\ncoffee;12;7 days").unwrap();
+        let table = csv.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+
+        // The price column is numeric, the note column has a non-numeric cell
+        assert_eq!(lines[0], "| item   | price | note   | ");
+        assert_eq!(lines[2], "| tea    |   4.5 | green  | ");
+        assert_eq!(lines[3], "| coffee |    12 | 7 days | ");
+    }
+
+    #[test]
+    fn test_csv_plain_output_has_no_colors() {
+        let csv = TextModifier::parse_csv("name;age\nAlice;30").unwrap();
+        assert_eq!(
+            csv.to_string(),
+            "| name  | age | \n|-------|-----|\n| Alice |  30 | \n"
+        );
+
+        // The colored table only adds escape codes around the header and separator. The override
+        // is global, so it is lifted again even if an assertion fails.
+        struct ColorOverride;
+        impl Drop for ColorOverride {
+            fn drop(&mut self) {
+                colored::control::unset_override();
+            }
+        }
+        colored::control::set_override(true);
+        let _override = ColorOverride;
+        let table = csv.to_string_colored();
+        assert!(table.contains("\u{1b}[1mname\u{1b}[0m"));
+        assert!(table.ends_with("| Alice |  30 | \n"));
+    }
+
+    #[test]
+    fn test_to_csv_string_quotes_delimiters() {
+        let csv = Csv {
+            headers: vec!["user".to_string(), "content".to_string()],
+            rows: vec![vec!["alice".to_string(), "hi, \"bob\"".to_string()]],
+        };
+        assert_eq!(
+            csv.to_csv_string(b',').unwrap(),
+            "user,content\nalice,\"hi, \"\"bob\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_transpose_turns_rows_into_columns() {
+        let csv = TextModifier::parse_csv("name;age;city\nAlice;30;Brno").unwrap();
+        let transposed = csv.transpose();
+
+        assert_eq!(transposed.headers, vec!["name", "Alice"]);
+        assert_eq!(
+            transposed.rows,
+            vec![vec!["age", "30"], vec!["city", "Brno"]]
+        );
+    }
+
+    #[test]
+    fn test_csv_transpose_pads_ragged_rows() {
+        let csv = Csv {
+            headers: vec!["a".to_string(), "b".to_string()],
+            rows: vec![
+                vec!["1".to_string()],
+                vec!["2".to_string(), "3".to_string(), "4".to_string()],
+            ],
+        };
+        let transposed = csv.transpose();
+
+        assert_eq!(transposed.headers, vec!["a", "1", "2"]);
+        assert_eq!(transposed.rows, vec![vec!["b", "", "3"], vec!["", "", "4"]]);
+    }
+
+    #[test]
+    fn test_csv_to_html_escapes_cells() {
+        let csv = TextModifier::parse_csv("tag;meaning\n<b>;bold & strong\n<i>;italic").unwrap();
+        let html = csv.to_html();
+
+        assert!(html.starts_with("<table>\n  <thead>\n    <tr><th>tag</th><th>meaning</th></tr>"));
+        assert!(html.contains("<tr><td>&lt;b&gt;</td><td>bold &amp; strong</td></tr>"));
+        assert!(!html.contains("<b>"));
+        assert_eq!(html.matches("<tr>").count(), 3);
+        assert!(html.ends_with("</tbody>\n</table>"));
+    }
+
+    #[test]
+    fn test_csv_group_by_aggregates_sales() {
+        let csv = TextModifier::parse_csv(
+            "region;product;amount\nNorth;tea;10\nSouth;coffee;5.5\nNorth;coffee;2.5",
+        )
+        .unwrap();
+
+        let Modifier::CsvGroupBy(key_col, agg_col, agg) = "csv-groupby:1:sum:3".parse().unwrap()
+        else {
+            panic!("Expected a group-by modifier");
+        };
+        let sums = csv.group_by(key_col, agg_col, agg).unwrap();
+        assert_eq!(sums.headers, vec!["region", "sum(amount)"]);
+        assert_eq!(sums.rows, vec![vec!["North", "12.5"], vec!["South", "5.5"]]);
+
+        let means = csv.group_by(0, 2, Agg::Mean).unwrap();
+        assert_eq!(means.rows[0], vec!["North", "6.25"]);
+        let counts = csv.group_by(1, 0, Agg::Count).unwrap();
+        assert_eq!(counts.rows, vec![vec!["tea", "1"], vec!["coffee", "2"]]);
+
+        // Product names can be counted, but not summed
+        assert!(csv.group_by(0, 1, Agg::Sum).is_err());
+        assert!(csv.group_by(0, 3, Agg::Count).is_err());
+        assert!("csv-groupby:0:sum:3".parse::<Modifier>().is_err());
+    }
+
+    #[test]
+    fn test_csv_describe_summarizes_columns() {
+        let csv = TextModifier::parse_csv("city;temperature\nBrno;2\nPraha;\nOstrava;-1\nZlín;5")
+            .unwrap();
+        let summary = csv.describe();
+
+        assert_eq!(
+            summary.headers,
+            vec!["column", "count", "min", "max", "mean", "stddev"]
+        );
+        // Only the city column has text, the empty temperature isn't counted
+        assert_eq!(summary.rows[0], vec!["city", "4", "", "", "", ""]);
+        assert_eq!(
+            summary.rows[1],
+            vec!["temperature", "3", "-1", "5", "2", "3"]
+        );
+    }
+
+    #[test]
+    fn test_csv_join_matches_keys() {
+        let users = TextModifier::parse_csv("id;name\n1;Alice\n2;Bob\n3;Cyril").unwrap();
+        let orders = TextModifier::parse_csv("item;user\ntea;1\ncake;4\ncoffee;1").unwrap();
+        let joined = users.join(&orders, 0, 1);
+
+        // Alice has two orders, Bob and Cyril none, and nobody has the id 4
+        assert_eq!(joined.headers, vec!["id", "name", "item"]);
+        assert_eq!(
+            joined.rows,
+            vec![vec!["1", "Alice", "tea"], vec!["1", "Alice", "coffee"]]
+        );
+    }
+
+    #[test]
+    fn test_between_ignores_unbalanced_open() {
+        assert_eq!(TextModifier::between("(x)y(z", '(', ')'), "x");
+        assert_eq!(TextModifier::between("no delimiters", '(', ')'), "");
+    }
+}
@@ -1,178 +1,11 @@
-use csv::ReaderBuilder;
 use flume::{Receiver, Sender};
-use slug::slugify;
-use std::io::{self, Write};
-use std::str::FromStr;
+use lesson_02::{
+    execute_pipeline, split_pipeline, Csv, Modifier, OperationError, TextModifier, MODIFIERS_USAGE,
+};
+use std::io::{self, IsTerminal, Write};
 use std::thread::{sleep, spawn};
 use std::time::Duration;
-use std::{env, error::Error, fmt, fs, iter, process::exit};
-
-// Custom Error type for the operations
-#[derive(Debug)]
-struct OperationError(String);
-
-impl fmt::Display for OperationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Operation Error: {}", self.0)
-    }
-}
-
-impl Error for OperationError {}
-
-// Csv struct to store headers and rows
-struct Csv {
-    headers: Vec<String>,
-    rows: Vec<Vec<String>>,
-}
-
-// Implementing the Display trait for Csv from: https://doc.rust-lang.org/std/fmt/trait.Display.html#examples
-impl fmt::Display for Csv {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Calculate maximum width for each column
-        let max_widths: Vec<usize> = self
-            .headers
-            .iter()
-            .enumerate()
-            .map(|(e, header)| {
-                iter::once(header.len())
-                    .chain(self.rows.iter().map(|row| row[e].len()))
-                    .max()
-                    .unwrap()
-            })
-            .collect();
-
-        println!("\nCSV output: \n");
-
-        // Display headers
-        write_row(f, &self.headers, &max_widths)?;
-
-        // Display separator line
-        write_separator(f, &max_widths)?;
-
-        // Dispaly rows
-        for row in &self.rows {
-            write_row(f, row, &max_widths)?;
-        }
-
-        Ok(())
-    }
-}
-
-fn write_row(f: &mut fmt::Formatter<'_>, row: &[String], max_widths: &[usize]) -> fmt::Result {
-    write!(f, "| ")?;
-    for (field, &width) in row.iter().zip(max_widths) {
-        write!(f, "{:<width$} | ", field, width = width)?;
-    }
-    writeln!(f)
-}
-
-fn write_separator(f: &mut fmt::Formatter<'_>, max_widths: &[usize]) -> fmt::Result {
-    write!(f, "|")?;
-    for &width in max_widths {
-        write!(f, "{:-<width$}|", "", width = width + 2)?;
-    }
-    writeln!(f)
-}
-
-#[derive(Debug)]
-enum Modifier {
-    Lowercase,
-    Uppercase,
-    NoSpaces,
-    Slugify,
-    Reverse,
-    Rot13,
-    Csv,
-}
-
-impl FromStr for Modifier {
-    type Err = OperationError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "lowercase" => Ok(Modifier::Lowercase),
-            "uppercase" => Ok(Modifier::Uppercase),
-            "no-spaces" => Ok(Modifier::NoSpaces),
-            "slugify" => Ok(Modifier::Slugify),
-            "reverse" => Ok(Modifier::Reverse),
-            "rot13" => Ok(Modifier::Rot13),
-            "csv" => Ok(Modifier::Csv),
-            _ => Err(OperationError(format!("Unknown modifier '{}'", s))),
-        }
-    }
-}
-
-struct TextModifier;
-
-impl TextModifier {
-    pub fn apply_lowercase(input: &str) -> String {
-        input.to_lowercase()
-    }
-
-    pub fn apply_uppercase(input: &str) -> String {
-        input.to_uppercase()
-    }
-
-    pub fn remove_spaces(input: &str) -> String {
-        input.replace(' ', "")
-    }
-
-    pub fn apply_slugify(input: &str) -> String {
-        slugify(input)
-    }
-
-    pub fn apply_reverse(input: &str) -> String {
-        input.chars().rev().collect()
-    }
-
-    pub fn apply_rot13(input: &str) -> String {
-        input
-            .chars()
-            .map(|c| {
-                if c.is_ascii_alphabetic() {
-                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                    (((c as u8 - base + 13) % 26) + base) as char
-                } else {
-                    c
-                }
-            })
-            .collect()
-    }
-
-    pub fn parse_csv(input: &str) -> Result<Csv, Box<dyn Error>> {
-        let mut reader = ReaderBuilder::new()
-            .has_headers(false) // default value is true and then we miss the first row (headers)
-            .delimiter(b';')
-            .from_reader(input.as_bytes());
-        let records = reader.records().collect::<Result<Vec<_>, _>>()?;
-
-        let headers: Vec<String> = records
-            .get(0)
-            .ok_or_else(|| "CSV must have at least one row".to_string())?
-            .iter()
-            .map(|field| field.to_string())
-            .collect();
-
-        let rows: Vec<Vec<String>> = records[1..]
-            .iter()
-            .map(|record| record.iter().map(|field| field.to_string()).collect())
-            .collect();
-
-        Ok(Csv { headers, rows })
-    }
-}
-
-fn execute_operation(modifier: Modifier, text: &str) -> Result<String, Box<dyn Error>> {
-    match modifier {
-        Modifier::Lowercase => Ok(TextModifier::apply_lowercase(text)),
-        Modifier::Uppercase => Ok(TextModifier::apply_uppercase(text)),
-        Modifier::NoSpaces => Ok(TextModifier::remove_spaces(text)),
-        Modifier::Slugify => Ok(TextModifier::apply_slugify(text)),
-        Modifier::Reverse => Ok(TextModifier::apply_reverse(text)),
-        Modifier::Rot13 => Ok(TextModifier::apply_rot13(text)),
-        Modifier::Csv => Ok(TextModifier::parse_csv(text)?.to_string()),
-    }
-}
+use std::{env, fs, process::exit};
 
 // MULTI-THREADING
 fn interactive_mode(tx: Sender<String>) {
@@ -202,15 +35,15 @@ fn interactive_mode(tx: Sender<String>) {
 fn processing_thread(rx: Receiver<String>) {
     loop {
         let input = rx.recv().unwrap();
-        let args: Vec<&str> = input.splitn(2, ' ').collect();
 
-        if args.len() != 2 {
-            eprintln!("Invalid input '{}'. Use format: <modifier> <text>.", input);
+        // Input may chain modifiers, e.g. "lowercase | reverse | rot13 'Hello World'"
+        let Some((modifier_strs, text)) = split_pipeline(&input) else {
+            eprintln!(
+                "Invalid input '{}'. Use format: <modifier> [| <modifier>...] <text>.",
+                input
+            );
             continue;
-        }
-
-        let modifier_str = args[0];
-        let text = args[1].trim();
+        };
 
         // Check if text contains more than one word without single quotes
         if !text.starts_with('\'') && !text.ends_with('\'') && text.split_whitespace().count() > 1 {
@@ -225,18 +58,51 @@ fn processing_thread(rx: Receiver<String>) {
             text
         };
 
-        match modifier_str.parse::<Modifier>() {
-            Ok(modifier) => match execute_operation(modifier, text) {
-                Ok(result) => println!("{}", result),
-                Err(err) => eprintln!("{}", err),
-            },
-            Err(_) => {
-                eprintln!("Unknown modifier. Valid modifiers: lowercase, uppercase, no-spaces, slugify, reverse, rot13, csv");
+        let stages = match modifier_strs
+            .iter()
+            .map(|modifier_str| Ok((*modifier_str, modifier_str.parse::<Modifier>()?)))
+            .collect::<Result<Vec<_>, OperationError>>()
+        {
+            Ok(stages) => stages,
+            Err(err) => {
+                eprintln!("{}. Valid modifiers: {}", err, MODIFIERS_USAGE);
+                continue;
+            }
+        };
+
+        match execute_pipeline(stages, text) {
+            Ok(result) => println!("{}", result),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
+// Reads and parses a CSV file, reporting failures on stderr
+fn read_csv(filename: &str) -> Option<Csv> {
+    match fs::read_to_string(filename) {
+        Ok(content) => match TextModifier::parse_csv(&content) {
+            Ok(csv) => Some(csv),
+            Err(err) => {
+                eprintln!("{}", err);
+                None
             }
+        },
+        Err(err) => {
+            eprintln!("Error reading file: {}", err);
+            None
         }
     }
 }
 
+fn print_csv(csv: &Csv) {
+    // Colors only help on a terminal, piped output stays plain
+    if io::stdout().is_terminal() {
+        println!("\nCSV output: \n\n{}", csv.to_string_colored());
+    } else {
+        println!("\nCSV output: \n\n{}", csv);
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -253,17 +119,26 @@ fn main() {
         }
     } else if args.len() == 2 {
         // For this to work, input 'cargo run example.csv' or use your cvs file.
-        let filename = &args[1];
-
-        match fs::read_to_string(filename) {
-            Ok(content) => match TextModifier::parse_csv(&content) {
-                Ok(csv) => println!("{}", csv),
-                Err(err) => eprintln!("{}", err),
-            },
-            Err(err) => {
-                eprintln!("Error reading file: {}", err);
+        if let Some(csv) = read_csv(&args[1]) {
+            print_csv(&csv);
+        }
+    } else if args.len() == 5 && args[3] == "--join-on" {
+        // Inner join of two files on a column both have, e.g. 'cargo run -- a.csv b.csv --join-on 1'
+        let column = match args[4].parse::<usize>() {
+            Ok(column) if column > 0 => column - 1,
+            _ => {
+                eprintln!("Invalid column '{}', counting starts at 1", args[4]);
+                exit(1);
             }
+        };
+        let (Some(left), Some(right)) = (read_csv(&args[1]), read_csv(&args[2])) else {
+            exit(1);
+        };
+        if column >= left.headers.len().min(right.headers.len()) {
+            eprintln!("Both files need a column {} to join on", column + 1);
+            exit(1);
         }
+        print_csv(&left.join(&right, column, column));
     } else {
         eprintln!("Invalid number of arguments. Usage: <modifier> <text>");
         exit(1);
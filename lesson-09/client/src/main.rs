@@ -1,7 +1,6 @@
 use std::{
     env,
     error::Error,
-    fmt,
     fs::File,
     io::{self, Cursor, Read, Write},
     net::TcpStream,
@@ -9,28 +8,7 @@ use std::{
 };
 
 use image::ImageOutputFormat;
-use serde_derive::{Deserialize, Serialize};
-
-// Custom Error type for the operations
-#[derive(Debug)]
-struct OperationError(String);
-
-impl fmt::Display for OperationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Operation Error: {}", self.0)
-    }
-}
-
-impl Error for OperationError {}
-
-// Define message types using serde serialization
-#[derive(Serialize, Deserialize, Debug)]
-enum MessageType {
-    File(String, Vec<u8>), // Filename and its content as bytes
-    Image(Vec<u8>),
-    Text(String),
-    Quit,
-}
+use shared::MessageType;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = env::args().collect();
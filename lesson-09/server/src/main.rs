@@ -2,7 +2,6 @@ use std::{
     collections::HashMap,
     env,
     error::Error,
-    fmt,
     fs::File,
     io::{Read, Write},
     net::{SocketAddr, TcpListener, TcpStream},
@@ -10,28 +9,7 @@ use std::{
     time::SystemTime,
 };
 
-use serde_derive::{Deserialize, Serialize};
-
-// Custom Error type for the operations
-#[derive(Debug)]
-struct OperationError(String);
-
-impl fmt::Display for OperationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Operation Error: {}", self.0)
-    }
-}
-
-impl Error for OperationError {}
-
-// Define message types using serde serialization
-#[derive(Serialize, Deserialize, Debug)]
-enum MessageType {
-    Text(String),
-    Image(Vec<u8>),
-    File(String, Vec<u8>), // Filename and its content as bytes
-    Quit,
-}
+use shared::MessageType;
 
 struct Server {
     address: Option<String>,
@@ -63,14 +41,14 @@ impl Server {
         Ok(())
     }
 
-    fn handle_client(&self, mut stream: TcpStream, clients: &mut HashMap<SocketAddr, TcpStream>) {
-        if let Some(message) = receive_message(&mut stream) {
+    fn handle_client(&self, stream: TcpStream, clients: &mut HashMap<SocketAddr, TcpStream>) {
+        if let Some(message) = receive_message(&stream) {
             match &message {
-                MessageType::File(filename, content) => {
+                MessageType::File(..) => {
                     println!("I am in file block!");
                     self.receive_file(&message, "../files/");
                 }
-                MessageType::Image(content) => {
+                MessageType::Image(_) => {
                     println!("Received image");
                     self.receive_file(&message, "../images/");
                 }
@@ -98,7 +76,7 @@ impl Server {
             let filepath = format!("{}{}_{}", directory, timestamp, filename);
 
             let mut file = File::create(&filepath).unwrap();
-            file.write_all(&content).unwrap();
+            file.write_all(content).unwrap();
 
             println!("Received file: {}", filepath);
         }
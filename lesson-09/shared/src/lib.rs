@@ -0,0 +1,22 @@
+// shared/lib.rs
+use serde_derive::{Deserialize, Serialize};
+
+/// Message types exchanged between the client and the server. Both binaries use this definition,
+/// so a message serialized by one is deserialized into the same variant by the other.
+///
+/// ```
+/// use shared::MessageType;
+///
+/// // What the client sends is what the server receives
+/// let sent = MessageType::File("notes.txt".to_string(), b"Hello".to_vec());
+/// let bytes = bincode::serialize(&sent).unwrap();
+/// let received: MessageType = bincode::deserialize(&bytes).unwrap();
+/// assert!(matches!(received, MessageType::File(name, content) if name == "notes.txt" && content == b"Hello"));
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub enum MessageType {
+    File(String, Vec<u8>), // Filename and its content as bytes
+    Image(Vec<u8>),
+    Text(String),
+    Quit,
+}
@@ -52,11 +52,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Build the server address from hostname and port
     let server_address = format!("{}:{}", hostname, port);
 
+    run_client(&server_address)
+}
+
+// Connects to the server on the given address and sends the messages read from stdin until
+// '.quit' is entered
+pub fn run_client(address: &str) -> Result<(), Box<dyn Error>> {
     // Connect to the server
-    let mut stream = TcpStream::connect(server_address.clone())?;
+    let mut stream = TcpStream::connect(address)?;
 
     // Log the successful connection to the server
-    info!("Connected to server on {}", server_address);
+    info!("Connected to server on {}", address);
 
     // Read user input and send messages to the server
     loop {
@@ -109,3 +115,20 @@ fn read_and_convert_image(path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
 
     Ok(png_bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::run_client;
+
+    #[test]
+    fn test_run_client_signature() {
+        let _: fn(&str) -> Result<(), Box<dyn Error>> = run_client;
+    }
+
+    #[test]
+    fn test_run_client_reports_unreachable_server() {
+        assert!(run_client("not an address").is_err());
+    }
+}
@@ -1,18 +1,24 @@
 use std::{
     collections::HashMap,
     env,
+    error::Error,
     fs::File,
     io::Write,
     net::{SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
     time::SystemTime,
 };
 
+use clap::{App, Arg};
 use log::{error, info};
-use tracing::{debug, instrument};
-use tracing_subscriber::fmt;
+use tracing::{debug, instrument, Level};
+use tracing_subscriber::{fmt, EnvFilter};
 
 use shared::{receive_message, MessageType};
 
+// Address the server listens on when none is given
+const DEFAULT_ADDRESS: &str = "localhost:11111";
+
 #[derive(Debug)]
 struct Server {
     #[allow(dead_code)] // Allowing unused code for the address field for future use
@@ -26,15 +32,12 @@ impl Server {
     }
 
     #[instrument]
-    fn start(&self, bind_address: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-        // Initialize tracing
-        fmt::init();
-
+    fn start(&self, bind_address: Option<&str>) -> Result<(), Box<dyn Error>> {
         // Create a TcpListener based on the provided or default bind_address
         let listener = match bind_address {
-            Some(addr) if addr == "0.0.0.0" => TcpListener::bind("0.0.0.0:11111")?, // Allow connections from any IP
+            Some("0.0.0.0") => TcpListener::bind("0.0.0.0:11111")?, // Allow connections from any IP
             Some(addr) => TcpListener::bind(addr)?,
-            None => TcpListener::bind("localhost:11111")?, // Default to localhost:11111
+            None => TcpListener::bind(DEFAULT_ADDRESS)?, // Default to localhost:11111
         };
 
         // Log the address the server is listening on
@@ -57,17 +60,17 @@ impl Server {
     }
 
     #[instrument]
-    fn handle_client(&self, mut stream: TcpStream, clients: &mut HashMap<SocketAddr, TcpStream>) {
+    fn handle_client(&self, stream: TcpStream, clients: &mut HashMap<SocketAddr, TcpStream>) {
         // Attempt to receive a message from the client
-        if let Some(message) = receive_message(&mut stream) {
+        if let Some(message) = receive_message(&stream) {
             // Process the received message based on its type
             match message {
                 MessageType::File(ref filename, ref content) => {
-                    self.receive_file(&filename, &content, "../files/");
+                    self.receive_file(filename, content, "../files/");
                 }
                 MessageType::Image(ref content) => {
                     info!("Received image");
-                    self.receive_file("received_image", &content, "../images/");
+                    self.receive_file("received_image", content, "../images/");
                 }
                 MessageType::Text(ref text) => {
                     info!("Received text message: {}", text);
@@ -104,16 +107,102 @@ impl Server {
     }
 }
 
-fn main() {
-    // Collect CL arguments
-    let args: Vec<String> = env::args().collect();
+// Builds the log filter: the level given on the command line if any, otherwise the `RUST_LOG`
+// environment variable if set, otherwise `info`
+fn log_filter(level: Option<&str>) -> Result<EnvFilter, String> {
+    match level {
+        Some(level) => {
+            let level = Level::from_str(level).map_err(|_| {
+                format!(
+                    "Invalid log level '{}', expected one of trace, debug, info, warn, error",
+                    level
+                )
+            })?;
+            Ok(EnvFilter::new(level.as_str()))
+        }
+        None => match env::var(EnvFilter::DEFAULT_ENV) {
+            Ok(directives) => EnvFilter::try_new(&directives).map_err(|err| {
+                format!(
+                    "Invalid {} '{}': {}",
+                    EnvFilter::DEFAULT_ENV,
+                    directives,
+                    err
+                )
+            }),
+            Err(_) => Ok(EnvFilter::new(Level::INFO.as_str())),
+        },
+    }
+}
 
+// Runs the server on the given address until it fails, e.g. because the address is taken
+pub fn run_server(address: &str) -> Result<(), Box<dyn Error>> {
     // Create a new Server instance with no specified address
     let server = Server::new(None);
 
-    // Start the server with the provided or default bind_address
-    if let Err(err) = server.start(args.get(1).map(|s| s.as_str())) {
+    // Start the server with the provided bind_address
+    server.start(Some(address))
+}
+
+fn main() {
+    // Parse command-line arguments using Clap
+    let matches = App::new("Server")
+        .about("Server application for the chat")
+        .arg(
+            Arg::with_name("address")
+                .value_name("ADDRESS")
+                .help("Sets the address to listen on (default: localhost:11111)"),
+        )
+        .arg(
+            Arg::with_name("log-level")
+                .long("log-level")
+                .value_name("LEVEL")
+                .help("Sets the log level: trace, debug, info, warn or error (default: RUST_LOG or info)")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    // Initialize tracing with the requested log level
+    match log_filter(matches.value_of("log-level")) {
+        Ok(filter) => fmt().with_env_filter(filter).init(),
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    }
+
+    // Run the server with the provided or default address
+    if let Err(err) = run_server(matches.value_of("address").unwrap_or(DEFAULT_ADDRESS)) {
         // Log an error if there is an issue starting the server
         error!("Server error: {}", err);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::{log_filter, run_server};
+
+    #[test]
+    fn test_run_server_signature() {
+        let _: fn(&str) -> Result<(), Box<dyn Error>> = run_server;
+    }
+
+    #[test]
+    fn test_run_server_reports_invalid_address() {
+        assert!(run_server("not an address").is_err());
+    }
+
+    #[test]
+    fn test_log_filter_accepts_all_levels() {
+        for level in ["trace", "debug", "info", "warn", "error"] {
+            assert!(log_filter(Some(level)).is_ok(), "{}", level);
+        }
+    }
+
+    #[test]
+    fn test_log_filter_rejects_invalid_level() {
+        let err = log_filter(Some("loud")).unwrap_err();
+        assert!(err.contains("Invalid log level 'loud'"), "{}", err);
+    }
+}
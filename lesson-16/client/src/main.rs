@@ -1,39 +1,66 @@
 // client/src/main.rs
 
-use std::io;
+//! # Client Main Module
+//!
+//! This module contains the main entry point for the client application. It handles command-line
+//! argument parsing, establishes a connection to the server, and manages user input to send messages.
+//!
+//! ## Examples
+//!
+//! ```
+//! // Run the client with default settings
+//! cargo run
+//!
+//! // Specify a custom server hostname and port
+//! cargo run -- --hostname hostexample --port 12345
+//! ```
 
-use anyhow::{Context, Result}; // Use anyhow for better error handling
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result}; // Use anyhow for better error handling
 use clap::{App, Arg}; // Clap for command-line argument parsing
-use tokio::io::{self as tokio_io, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader}; // tokio for async programming
+use image::{imageops::FilterType, ImageFormat, ImageOutputFormat};
+use tokio::io::{
+    self as tokio_io, AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt,
+    BufReader,
+}; // tokio for async programming
 use tokio::net::TcpStream;
+use tokio::sync::Mutex;
 use tokio::task;
 
-use shared::MessageType; // Shared module with message types and file sending logic
+use shared::{
+    receive_file, receive_message, send_directory, send_file, send_framed, set_message_format,
+    sha256_hex, socket_address, tls, unix_time, Capabilities, MessageFormat, MessageType,
+    ReceiveError, DEFAULT_PORT, PROTOCOL_VERSION, WIRE_FORMAT,
+}; // Shared module with message types and file sending logic
 
-/// # Client Main Module
-///
-/// This module contains the main entry point for the client application. It handles command-line
-/// argument parsing, establishes a connection to the server, and manages user input to send messages.
-///
-/// ## Examples
-///
-/// ```
-/// // Run the client with default settings
-/// cargo run
-///
-/// // Specify a custom server hostname and port
-/// cargo run -- --hostname hostexample --port 12345
-/// ```
+/// Number of messages requested by `.history` when no number is given.
+const DEFAULT_HISTORY_LEN: u32 = 10;
+
+/// Directory files downloaded with `.get` are saved to, unless configured otherwise.
+const DEFAULT_DOWNLOAD_DIR: &str = "downloads";
+
+/// Connection to the server, either a plain `TcpStream` or one wrapped in TLS.
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Connection for S {}
 
 /// # Async Helper Function to Send a Message
 ///
-/// This function serializes and sends a message to the server over the provided TcpStream.
+/// This function serializes and sends a message to the server over the provided stream,
+/// using the length-prefixed framing of `shared::send_framed`.
 /// It returns a Result indicating success or failure, with an `anyhow::Error` providing
 /// additional context in case of failure.
 ///
 /// # Arguments
 ///
-/// * `stream` - A mutable reference to a TcpStream representing the connection to the server.
+/// * `stream` - A mutable reference to anything implementing `AsyncWrite`, e.g. a TcpStream, its
+///   write half or an in-memory buffer.
 /// * `message` - The message to be sent to the server, encapsulated in the `MessageType` enum.
 ///
 /// # Example
@@ -47,36 +74,67 @@ use shared::MessageType; // Shared module with message types and file sending lo
 /// let result = send_message(&mut stream, &message).await;
 /// assert!(result.is_ok());
 /// ```
-pub async fn send_message(
-    stream: &mut TcpStream,
-    message: &MessageType,
-) -> Result<(), anyhow::Error> {
-    let serialized_message = bincode::serialize(&message)
-        .with_context(|| format!("Failed to serialize message: {:?}", message))?;
-
-    stream
-        .write_all(&serialized_message)
+pub async fn send_message<W>(stream: &mut W, message: &MessageType) -> Result<(), anyhow::Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    send_framed(stream, message)
         .await
         .with_context(|| format!("Failed to send message: {:?}", message))?;
 
     Ok(())
 }
 
-// Helper function to read and convert image content to PNG format
+/// Quality of JPEG images when none is given with `--jpeg-quality`.
+const DEFAULT_JPEG_QUALITY: u8 = 80;
+
+/// Maximum number of pixels of an image the client decodes, unless configured otherwise. A small
+/// compressed file can declare a huge bitmap, so larger images are refused before decoding.
+const DEFAULT_MAX_PIXELS: u64 = 64 * 1024 * 1024;
+
+/// # Parse Image Format
+///
+/// This function turns the `--image-format` option into the format images are sent in.
+///
+/// # Arguments
+///
+/// * `name` - `png`, `jpeg` (or `jpg`) or `original`.
+/// * `quality` - The JPEG quality from 1 to 100, ignored for other formats.
+///
+/// # Returns
+///
+/// A `Result` containing the `ImageOutputFormat` to convert images to, or `None` to send images
+/// unchanged, or an `anyhow::Error` if the format is unknown.
+fn parse_image_format(name: &str, quality: u8) -> Result<Option<ImageOutputFormat>> {
+    match name.to_lowercase().as_str() {
+        "png" => Ok(Some(ImageOutputFormat::Png)),
+        "jpeg" | "jpg" => Ok(Some(ImageOutputFormat::Jpeg(quality))),
+        "original" => Ok(None),
+        _ => bail!("Unknown image format: {}", name),
+    }
+}
+
+// Helper function to read and convert image content to the chosen format
 /// # Read and Convert Image
 ///
-/// This asynchronous function reads an image file from the specified path, converts it to the PNG
-/// format, and returns the resulting bytes as a `Vec<u8>`. The function uses Tokio's `spawn_blocking`
-/// to perform blocking operations, such as opening the image file, without blocking the Tokio runtime.
+/// This asynchronous function reads an image file from the specified path, converts it to the
+/// given format, and returns the resulting bytes as a `Vec<u8>`. The function uses Tokio's
+/// `spawn_blocking` to perform blocking operations, such as opening the image file, without
+/// blocking the Tokio runtime.
 ///
 /// # Arguments
 ///
 /// * `path` - A string slice representing the path to the image file.
+/// * `format` - The format to convert the image to, or `None` to keep the original file content.
+/// * `max_dim` - The maximum width and height in pixels. Larger images are scaled down, keeping
+///   their aspect ratio, and then encoded in `format` or their original format.
+/// * `max_pixels` - The maximum number of pixels of an image to decode. Images declaring more
+///   pixels are refused before they are decoded.
 ///
 /// # Returns
 ///
-/// A `Result` containing the PNG-encoded image bytes if successful, or an `anyhow::Error` in case
-/// of failure.
+/// A `Result` containing the encoded image bytes if successful, or an `anyhow::Error` in case
+/// of failure or if the image exceeds `max_pixels`.
 ///
 /// # Example
 ///
@@ -87,29 +145,494 @@ pub async fn send_message(
 /// #[tokio::main]
 /// async fn main() -> Result<()> {
 ///     let path = "path/to/image.jpg";
-///     let png_bytes = read_and_convert_image(path).await?;
+///     let png_bytes = read_and_convert_image(path, Some(ImageOutputFormat::Png), None, DEFAULT_MAX_PIXELS)
+///         .await?;
 ///     println!("Image converted to PNG with {} bytes", png_bytes.len());
 ///     Ok(())
 /// }
 /// ```
-async fn read_and_convert_image(path: &str) -> Result<Vec<u8>> {
+async fn read_and_convert_image(
+    path: &str,
+    format: Option<ImageOutputFormat>,
+    max_dim: Option<u32>,
+    max_pixels: u64,
+) -> Result<Vec<u8>> {
+    let read_original = || async {
+        tokio::fs::read(path)
+            .await
+            .with_context(|| format!("Failed to read image at {}", path))
+    };
+    if format.is_none() && max_dim.is_none() {
+        return read_original().await;
+    }
     let path_clone = path.to_owned(); // Clone path before moving into closure
 
     let image_result = task::spawn_blocking(move || {
+        check_image_size(&path_clone, max_pixels)?;
         image::open(&path_clone).with_context(|| format!("Failed to open image at {}", &path_clone))
     })
     .await?;
 
     let image = image_result?;
 
-    let mut png_bytes = Vec::new();
-    let mut cursor = io::Cursor::new(&mut png_bytes);
+    // Scale the image down when either side exceeds the maximum dimension
+    let too_large = max_dim.filter(|&max_dim| image.width() > max_dim || image.height() > max_dim);
+    let image = match too_large {
+        Some(max_dim) => image.resize(max_dim, max_dim, FilterType::Lanczos3),
+        None => image,
+    };
+
+    // Images that are neither converted nor scaled down are sent unchanged
+    let format = match format {
+        Some(format) => format,
+        None if too_large.is_none() => return read_original().await,
+        None => ImageFormat::from_path(path)
+            .with_context(|| format!("Unknown image format of {}", path))?
+            .into(),
+    };
+
+    let mut bytes = Vec::new();
+    let mut cursor = io::Cursor::new(&mut bytes);
 
     image
-        .write_to(&mut cursor, image::ImageOutputFormat::Png)
-        .with_context(|| "Failed to convert image to PNG format")?;
+        .write_to(&mut cursor, format.clone())
+        .with_context(|| format!("Failed to convert image to {:?} format", format))?;
 
-    Ok(png_bytes)
+    Ok(bytes)
+}
+
+/// # Image Name
+///
+/// This function returns the name an image is sent under: its file name, with the extension
+/// replaced by the one of the format it is converted to.
+///
+/// # Arguments
+///
+/// * `path` - A string slice representing the path to the image file.
+/// * `format` - The format the image is converted to, or `None` if it keeps its original format.
+fn image_name(path: &str, format: Option<&ImageOutputFormat>) -> String {
+    let path = Path::new(path);
+    let name = match format {
+        Some(ImageOutputFormat::Jpeg(_)) => path.with_extension("jpg"),
+        Some(_) => path.with_extension("png"),
+        None => path.to_path_buf(),
+    };
+    name.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "image".to_string())
+}
+
+/// # Print Progress
+///
+/// This function overwrites the current stderr line with the progress of a file transfer.
+///
+/// # Arguments
+///
+/// * `sent` - The number of bytes sent so far.
+/// * `total` - The total number of bytes to send.
+fn print_progress(sent: u64, total: u64) {
+    let percent = (sent * 100).checked_div(total).unwrap_or(100);
+    eprint!("\r{} / {} bytes ({}%)", sent, total, percent);
+    let _ = io::stderr().flush();
+}
+
+/// # Print Incoming Messages
+///
+/// This asynchronous function reads messages from the server until the connection closes and
+/// prints them: text messages relayed from other clients, acknowledgements of sent messages and
+/// answers to admin requests. It runs in its own task, so messages show up while the user types.
+/// Pings of the server are answered right away without printing anything, and files downloaded
+/// with `.get` are saved to `downloads`.
+///
+/// # Arguments
+///
+/// * `reader` - The read half of the connection to the server.
+/// * `writer` - The write half of the connection to the server, used to answer pings.
+/// * `out` - Where the messages are printed, usually stdout.
+/// * `downloads` - The directory downloaded files are saved to.
+///
+/// # Returns
+///
+/// A `Result` indicating success or an `io::Error` if a message can't be printed.
+async fn print_incoming<R, W, O>(
+    reader: &mut R,
+    writer: &Mutex<W>,
+    out: &mut O,
+    downloads: &str,
+) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+    O: Write,
+{
+    loop {
+        match receive_message(reader).await {
+            Ok(MessageType::Ping) => {
+                if let Err(err) = send_framed(&mut *writer.lock().await, &MessageType::Pong).await {
+                    eprintln!("Failed to answer a ping from the server: {}", err);
+                }
+                continue;
+            }
+            Ok(MessageType::Pong) => continue,
+            Ok(MessageType::Ack(id)) => writeln!(out, "Message acknowledged (#{})", id)?,
+            Ok(MessageType::Text(text)) | Ok(MessageType::TextWithTime(text, _)) => {
+                writeln!(out, "{}", text)?
+            }
+            Ok(MessageType::VerifyReport {
+                ok,
+                corrupt,
+                unverified,
+            }) => {
+                writeln!(out, "Verified files: {} ok", ok)?;
+                for name in corrupt {
+                    writeln!(out, "corrupt: {}", name)?;
+                }
+                for name in unverified {
+                    writeln!(out, "unverified: {}", name)?;
+                }
+            }
+            Ok(MessageType::File(name, content, checksum)) => {
+                match save_download(&name, &content, &checksum, downloads) {
+                    Ok(path) => writeln!(out, "Downloaded {} to {}", name, path.display())?,
+                    Err(err) => eprintln!("Failed to download {}: {:#}", name, err),
+                }
+            }
+            Ok(MessageType::FileList(names)) => {
+                writeln!(out, "Files on the server: {}", names.len())?;
+                for name in names {
+                    writeln!(out, "{}", name)?;
+                }
+            }
+            Ok(other) => eprintln!("Unexpected message from the server: {:?}", other),
+            Err(ReceiveError::Closed) => {
+                writeln!(out, "Disconnected from the server")?;
+                return Ok(());
+            }
+            Err(err) => {
+                eprintln!("Failed to receive a message from the server: {}", err);
+                return Ok(());
+            }
+        }
+        out.flush()?;
+    }
+}
+
+/// Saves a file downloaded from the server to `directory` after checking its checksum. Only the
+/// last component of the name is used, so a file is never written outside `directory`.
+///
+/// # Returns
+///
+/// A `Result` containing the path the file was saved at, or an `anyhow::Error` if the checksum
+/// doesn't match or the file can't be written.
+fn save_download(
+    name: &str,
+    content: &[u8],
+    checksum: &str,
+    directory: &str,
+) -> Result<std::path::PathBuf> {
+    if sha256_hex(content) != checksum {
+        bail!("Checksum mismatch, the file is corrupted");
+    }
+    let filename = Path::new(name)
+        .file_name()
+        .with_context(|| format!("Not a file name: {}", name))?;
+    receive_file(&filename.to_string_lossy(), content, directory)
+}
+
+/// # Handshake
+///
+/// This asynchronous function performs the protocol handshake with the server: it sends the
+/// `PROTOCOL_VERSION` of the client and whether it wants to compress files in a
+/// `MessageType::Hello`, and waits for the `Hello` of the server.
+///
+/// # Arguments
+///
+/// * `stream` - The connection to the server, before any other message is sent.
+/// * `compression` - Whether the client asks to compress files.
+/// * `encrypted` - Whether the connection is wrapped in TLS.
+///
+/// # Returns
+///
+/// A `Result` containing the `Capabilities` negotiated with the server, or an `anyhow::Error`
+/// with the reason if the server refuses the client or the handshake fails.
+async fn handshake<S>(stream: &mut S, compression: bool, encrypted: bool) -> Result<Capabilities>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let hello = MessageType::Hello {
+        version: PROTOCOL_VERSION,
+        compression,
+    };
+    send_message(stream, &hello).await?;
+
+    match receive_message(stream).await {
+        Ok(MessageType::Hello {
+            version,
+            compression,
+        }) if version == PROTOCOL_VERSION => Ok(Capabilities::negotiated(compression, encrypted)),
+        Ok(MessageType::Hello { version, .. }) => bail!(
+            "The server speaks protocol version {}, this client speaks version {}",
+            version,
+            PROTOCOL_VERSION
+        ),
+        Ok(MessageType::Text(error)) => bail!("The server refused the connection: {}", error),
+        Ok(other) => bail!("Unexpected handshake reply from the server: {:?}", other),
+        Err(err) => Err(err).context("Failed to receive the handshake reply"),
+    }
+}
+
+/// Time `--send` waits for the server to acknowledge the message.
+const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// # Send Once
+///
+/// This asynchronous function sends a single text message and waits for the server to
+/// acknowledge it. Pings arriving in the meantime are answered, other messages are ignored.
+///
+/// # Arguments
+///
+/// * `stream` - The connection to the server.
+/// * `text` - The text message to send.
+///
+/// # Returns
+///
+/// A `Result` containing the id of the `MessageType::Ack`, or an `anyhow::Error` if the message
+/// can't be sent or isn't acknowledged within `ACK_TIMEOUT`.
+async fn send_once<S>(stream: &mut S, text: &str) -> Result<u64>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    send_message(stream, &MessageType::Text(text.to_string())).await?;
+
+    let wait_for_ack = async {
+        loop {
+            match receive_message(stream).await {
+                Ok(MessageType::Ack(id)) => return Ok(id),
+                Ok(MessageType::Ping) => send_message(stream, &MessageType::Pong).await?,
+                Ok(_) => {}
+                Err(ReceiveError::Closed) => {
+                    bail!("The server closed the connection before acknowledging the message")
+                }
+                Err(err) => return Err(err).context("Failed to receive the acknowledgement"),
+            }
+        }
+    };
+    tokio::time::timeout(ACK_TIMEOUT, wait_for_ack)
+        .await
+        .context("Timed out waiting for the acknowledgement")?
+}
+
+/// Time the client waits for the server to close the connection after `.quit`.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Waits for the server to close the connection after `.quit`, so the messages sent before are
+/// handled and their replies printed by the `incoming` task before the client exits.
+async fn wait_for_close(incoming: task::JoinHandle<()>) {
+    if tokio::time::timeout(CLOSE_TIMEOUT, incoming).await.is_err() {
+        eprintln!("The server didn't close the connection, exiting anyway");
+    }
+}
+
+/// Checks that the image at `path` doesn't exceed `max_pixels` by reading only the dimensions
+/// from its header, so an image declaring a huge bitmap is refused before it is decoded.
+///
+/// # Returns
+///
+/// A `Result` indicating success, or an `anyhow::Error` if the header can't be read or the image
+/// has too many pixels.
+fn check_image_size(path: &str, max_pixels: u64) -> Result<()> {
+    let (width, height) = image::io::Reader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .with_context(|| format!("Failed to open image at {}", path))?
+        .into_dimensions()
+        .with_context(|| format!("Failed to read the dimensions of the image at {}", path))?;
+
+    let pixels = u64::from(width) * u64::from(height);
+    if pixels > max_pixels {
+        bail!(
+            "Image at {} is {}x{} pixels, more than the limit of {} pixels",
+            path,
+            width,
+            height,
+            max_pixels
+        );
+    }
+    Ok(())
+}
+
+/// Options of the client that affect how commands are turned into messages.
+struct ClientOptions {
+    /// Capabilities negotiated for the connection.
+    capabilities: Capabilities,
+    /// Whether the progress output of file transfers is suppressed.
+    quiet: bool,
+    /// Format images are converted to before sending, `None` to send them unchanged.
+    image_format: Option<ImageOutputFormat>,
+    /// Maximum width and height of sent images.
+    max_dim: Option<u32>,
+    /// Maximum number of pixels of images decoded before sending.
+    max_pixels: u64,
+}
+
+/// # Run Command
+///
+/// This asynchronous function handles one line of user input: a command such as `.file` or
+/// `.image`, or a text message. The resulting messages are sent to the server.
+///
+/// # Arguments
+///
+/// * `input` - The trimmed line of input.
+/// * `stream` - The connection to the server, shared with the task answering pings.
+/// * `options` - The `ClientOptions` of the client.
+///
+/// # Returns
+///
+/// A `Result` containing `true` once `.quit` has been sent, or an `anyhow::Error` if the command
+/// is invalid or its message can't be sent.
+async fn run_command<W>(input: &str, stream: &Mutex<W>, options: &ClientOptions) -> Result<bool>
+where
+    W: AsyncWrite + Unpin,
+{
+    // Convert user input to a message based on commands or text
+    let message = match input {
+        ".quit" => MessageType::Quit,
+        ".caps" => {
+            println!("{}", options.capabilities);
+            return Ok(false);
+        }
+        _ => {
+            if input.starts_with(".file") {
+                // Files are sent in chunks, compressed one by one if negotiated
+                let path = input.trim_start_matches(".file").trim();
+                send_file(
+                    &mut *stream.lock().await,
+                    path,
+                    options.capabilities.compression,
+                    |sent, total| {
+                        if !options.quiet {
+                            print_progress(sent, total)
+                        }
+                    },
+                )
+                .await?;
+                if !options.quiet {
+                    eprintln!("\rSent {}: done", path);
+                }
+                return Ok(false);
+            } else if input.starts_with(".dir") {
+                // Every file of the directory is sent with its path relative to it
+                let path = input.trim_start_matches(".dir").trim();
+                let sent = send_directory(
+                    &mut *stream.lock().await,
+                    path,
+                    options.capabilities.compression,
+                )
+                .await?;
+                if !options.quiet {
+                    eprintln!("Sent {}: {} files", path, sent);
+                }
+                return Ok(false);
+            } else if input.starts_with(".image") {
+                let path = input.trim_start_matches(".image").trim();
+                let image_content = read_and_convert_image(
+                    path,
+                    options.image_format.clone(),
+                    options.max_dim,
+                    options.max_pixels,
+                )
+                .await
+                .context("Failed to read and convert image")?;
+                MessageType::Image(
+                    image_name(path, options.image_format.as_ref()),
+                    image_content,
+                )
+            } else if input.starts_with(".history") {
+                let limit = input.trim_start_matches(".history").trim();
+                if limit.is_empty() {
+                    MessageType::History(DEFAULT_HISTORY_LEN)
+                } else {
+                    match limit.parse() {
+                        Ok(limit) => MessageType::History(limit),
+                        Err(_) => bail!("Invalid number of messages: {}", limit),
+                    }
+                }
+            } else if input == ".ls" {
+                MessageType::ListFiles
+            } else if input.starts_with(".get") {
+                let name = input.trim_start_matches(".get").trim();
+                if name.is_empty() {
+                    bail!("Missing file name, use .get <name> with a name listed by .ls");
+                }
+                MessageType::GetFile(name.to_string())
+            } else if input.starts_with(".verify") {
+                let token = input.trim_start_matches(".verify").trim();
+                MessageType::Verify {
+                    token: token.to_string(),
+                }
+            } else {
+                MessageType::TextWithTime(input.to_string(), unix_time())
+            }
+        }
+    };
+
+    // Serialize and send the message to the server
+    send_message(&mut *stream.lock().await, &message).await?;
+
+    Ok(matches!(message, MessageType::Quit))
+}
+
+/// # Run Script
+///
+/// This asynchronous function runs the commands of a script, one per line, as if they were typed
+/// by the user. Blank lines are skipped. A failing line is reported with its line number and the
+/// script goes on with the next one. The script ends at `.quit`, which is sent at the end if the
+/// script doesn't contain it.
+///
+/// # Arguments
+///
+/// * `script` - The lines of the script.
+/// * `stream` - The connection to the server.
+/// * `options` - The `ClientOptions` of the client.
+/// * `errors` - Where failing lines are reported, usually stderr.
+///
+/// # Returns
+///
+/// A `Result` containing the number of failed lines, or an `anyhow::Error` if the script can't be
+/// read.
+async fn run_script<R, W, E>(
+    script: R,
+    stream: &Mutex<W>,
+    options: &ClientOptions,
+    errors: &mut E,
+) -> Result<usize>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+    E: Write,
+{
+    let mut lines = script.lines();
+    let mut line_number = 0;
+    let mut failed = 0;
+
+    while let Some(line) = lines.next_line().await.context("Failed to read script")? {
+        line_number += 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match run_command(line, stream, options).await {
+            Ok(true) => return Ok(failed),
+            Ok(false) => {}
+            Err(err) => {
+                failed += 1;
+                writeln!(errors, "Line {}: {:#}", line_number, err)?;
+            }
+        }
+    }
+
+    send_message(&mut *stream.lock().await, &MessageType::Quit).await?;
+    Ok(failed)
 }
 
 /// # Main Function
@@ -149,24 +672,215 @@ async fn main() -> Result<()> {
                 .help("Sets the server port")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("tls")
+                .long("tls")
+                .help("Encrypts the connection with TLS"),
+        )
+        .arg(
+            Arg::with_name("tls-ca")
+                .long("tls-ca")
+                .value_name("FILE")
+                .help("Trusts the certificates in the PEM file, e.g. the self-signed certificate of the server, instead of the web root certificates")
+                .takes_value(true)
+                .requires("tls"),
+        )
+        .arg(
+            Arg::with_name("compress")
+                .long("compress")
+                .help("Compresses file contents with gzip before sending"),
+        )
+        .arg(
+            Arg::with_name("image-format")
+                .long("image-format")
+                .value_name("FORMAT")
+                .help("Sets the format images are sent in: png (default), jpeg or original")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("jpeg-quality")
+                .long("jpeg-quality")
+                .value_name("QUALITY")
+                .help("Sets the quality of JPEG images from 1 to 100 (default: 80)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-dim")
+                .long("max-dim")
+                .value_name("PIXELS")
+                .help("Scales images down so neither side exceeds the given number of pixels")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-pixels")
+                .long("max-pixels")
+                .value_name("PIXELS")
+                .help("Refuses to decode images with more pixels than this (default: 67108864)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("download-dir")
+                .long("download-dir")
+                .value_name("DIR")
+                .help("Sets the directory files downloaded with .get are saved to (default: downloads)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("name")
+                .long("name")
+                .value_name("NAME")
+                .help("Sets the username instead of asking for it")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("send")
+                .long("send")
+                .value_name("TEXT")
+                .help("Sends a single text message, waits for it to be acknowledged and exits")
+                .takes_value(true)
+                .conflicts_with("script"),
+        )
+        .arg(
+            Arg::with_name("script")
+                .long("script")
+                .value_name("FILE")
+                .help("Runs the commands in the file, one per line, instead of reading them from the terminal")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("Suppresses the progress output of file transfers"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Sets the serialization of messages, the server has to use the same one")
+                .possible_values(&MessageFormat::NAMES)
+                .default_value(WIRE_FORMAT),
+        )
         .get_matches();
 
+    matches
+        .value_of("format")
+        .map_or(Ok(MessageFormat::default()), MessageFormat::parse)
+        .and_then(set_message_format)?;
+
     // Extract hostname and port from CL arguments or use defaults
     let (hostname, port) = match (
         matches.value_of("hostname").map(String::from),
         matches.value_of("port").map(String::from),
     ) {
         (Some(h), Some(p)) => (h, p.parse().context("Invalid port number")?),
-        _ => ("localhost".to_string(), 11111),
+        _ => ("localhost".to_string(), DEFAULT_PORT),
     };
 
     // Build the server address from hostname and port
-    let server_address = format!("{}:{}", hostname, port);
+    let server_address = socket_address(&hostname, port);
 
-    // Connect to the server
-    let mut stream = TcpStream::connect(server_address.clone())
+    // Connect to the server, encrypting the connection with TLS if requested
+    let stream = TcpStream::connect(server_address.clone())
         .await
         .with_context(|| format!("Failed to connect to the server at {}", server_address))?;
+    let encrypted = matches.is_present("tls");
+    let mut stream: Box<dyn Connection> = if encrypted {
+        let connector = tls::connector(matches.value_of("tls-ca"))?;
+        let stream = connector
+            .connect(tls::server_name(&hostname)?, stream)
+            .await
+            .with_context(|| format!("TLS handshake with {} failed", server_address))?;
+        Box::new(stream)
+    } else {
+        Box::new(stream)
+    };
+    let capabilities = handshake(&mut stream, matches.is_present("compress"), encrypted).await?;
+
+    // Send a single message and exit without entering the interactive loop
+    if let Some(text) = matches.value_of("send") {
+        let id = send_once(&mut stream, text).await?;
+        println!("Message acknowledged (#{})", id);
+        return Ok(());
+    }
+
+    // Print messages from the server in a separate task while the user types
+    let (mut reader, stream) = tokio_io::split(stream);
+    let stream = Arc::new(Mutex::new(stream));
+    let pong_writer = stream.clone();
+    let downloads = matches
+        .value_of("download-dir")
+        .unwrap_or(DEFAULT_DOWNLOAD_DIR)
+        .to_string();
+    let incoming = tokio::spawn(async move {
+        let out = &mut io::stdout();
+        if let Err(err) = print_incoming(&mut reader, &*pong_writer, out, &downloads).await {
+            eprintln!("Failed to print incoming messages: {}", err);
+        }
+    });
+
+    let quiet = matches.is_present("quiet");
+
+    // Format images are converted to before sending
+    let jpeg_quality = match matches.value_of("jpeg-quality") {
+        Some(quality) => match quality.parse() {
+            Ok(quality @ 1..=100) => quality,
+            _ => bail!("Invalid JPEG quality: {}", quality),
+        },
+        None => DEFAULT_JPEG_QUALITY,
+    };
+    let max_dim = match matches.value_of("max-dim") {
+        Some(max_dim) => match max_dim.parse() {
+            Ok(max_dim @ 1..) => Some(max_dim),
+            _ => bail!("Invalid maximum image dimension: {}", max_dim),
+        },
+        None => None,
+    };
+    let max_pixels = match matches.value_of("max-pixels") {
+        Some(max_pixels) => match max_pixels.parse() {
+            Ok(max_pixels @ 1..) => max_pixels,
+            _ => bail!("Invalid maximum number of image pixels: {}", max_pixels),
+        },
+        None => DEFAULT_MAX_PIXELS,
+    };
+    let image_format = parse_image_format(
+        matches.value_of("image-format").unwrap_or("png"),
+        jpeg_quality,
+    )?;
+    let options = ClientOptions {
+        capabilities,
+        quiet,
+        image_format,
+        max_dim,
+        max_pixels,
+    };
+
+    // Log in with a username before sending any other message
+    let mut name = matches.value_of("name").unwrap_or_default().to_string();
+    while name.is_empty() {
+        print!("Enter your name: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        BufReader::new(tokio_io::stdin())
+            .read_line(&mut input)
+            .await?;
+        name = input.trim().to_string();
+    }
+    send_message(&mut *stream.lock().await, &MessageType::Login(name)).await?;
+
+    // Run the commands of the script instead of reading them from the user
+    if let Some(path) = matches.value_of("script") {
+        let script = tokio::fs::File::open(path)
+            .await
+            .with_context(|| format!("Failed to open script {}", path))?;
+        let failed =
+            run_script(BufReader::new(script), &stream, &options, &mut io::stderr()).await?;
+        wait_for_close(incoming).await;
+        if failed > 0 {
+            bail!("{} line(s) of {} failed", failed, path);
+        }
+        return Ok(());
+    }
 
     // Read user input and send messages to the server
     loop {
@@ -175,45 +889,361 @@ async fn main() -> Result<()> {
         BufReader::new(tokio_io::stdin())
             .read_line(&mut input)
             .await?;
-        let input = input.trim();
-
-        // Convert user input to a message based on commands or text
-        let message = match input {
-            ".quit" => MessageType::Quit,
-            _ => {
-                if input.starts_with(".file") {
-                    let path = input.trim_start_matches(".file").trim();
-
-                    let mut file = tokio::fs::File::open(path)
-                        .await
-                        .with_context(|| format!("Failed to open file: {}", path))?;
-
-                    let mut file_content = Vec::new();
-                    file.read_to_end(&mut file_content)
-                        .await
-                        .with_context(|| format!("Failed to read file: {}", path))?;
-
-                    MessageType::File(path.to_string(), file_content)
-                } else if input.starts_with(".image") {
-                    let path = input.trim_start_matches(".image").trim();
-                    let image_content = read_and_convert_image(path)
-                        .await
-                        .context("Failed to read and convert image")?;
-                    MessageType::Image(image_content)
-                } else {
-                    MessageType::Text(input.to_string())
-                }
-            }
+
+        match run_command(input.trim(), &stream, &options).await {
+            // If the user wants to quit, break the loop
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(err) => eprintln!("{:#}", err),
+        }
+    }
+    wait_for_close(incoming).await;
+
+    Ok(())
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a small test image to `path`, in the format given by its extension.
+    fn write_test_image(path: &std::path::Path, width: u32, height: u32) {
+        image::RgbImage::from_pixel(width, height, image::Rgb([200, 100, 50]))
+            .save(path)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_and_convert_image_to_jpeg() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.png");
+        write_test_image(&path, 8, 8);
+
+        let format = parse_image_format("jpeg", 90).unwrap();
+        let bytes =
+            read_and_convert_image(path.to_str().unwrap(), format, None, DEFAULT_MAX_PIXELS)
+                .await
+                .unwrap();
+
+        // Every JPEG starts with the SOI marker followed by another marker
+        assert_eq!(&bytes[..3], &[0xFF, 0xD8, 0xFF]);
+    }
+
+    #[tokio::test]
+    async fn test_read_and_convert_image_keeps_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.png");
+        write_test_image(&path, 8, 8);
+
+        let bytes = read_and_convert_image(path.to_str().unwrap(), None, None, DEFAULT_MAX_PIXELS)
+            .await
+            .unwrap();
+
+        assert_eq!(bytes, std::fs::read(&path).unwrap());
+        assert!(parse_image_format("gif", 90).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_and_convert_image_scales_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let large = dir.path().join("large.png");
+        write_test_image(&large, 40, 20);
+        let small = dir.path().join("small.png");
+        write_test_image(&small, 8, 4);
+
+        // The longer side is scaled to the maximum, keeping the aspect ratio
+        let bytes =
+            read_and_convert_image(large.to_str().unwrap(), None, Some(10), DEFAULT_MAX_PIXELS)
+                .await
+                .unwrap();
+        let image = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (10, 5));
+
+        // Smaller images are left as they are
+        let format = parse_image_format("png", 90).unwrap();
+        let bytes = read_and_convert_image(
+            small.to_str().unwrap(),
+            format,
+            Some(10),
+            DEFAULT_MAX_PIXELS,
+        )
+        .await
+        .unwrap();
+        let image = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (8, 4));
+    }
+
+    #[tokio::test]
+    async fn test_read_and_convert_image_rejects_decompression_bomb() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bomb.ppm");
+        // A PPM header declaring a 100000x100000 bitmap, without any pixel data
+        std::fs::write(&path, b"P6\n100000 100000\n255\n").unwrap();
+
+        let format = parse_image_format("png", 90).unwrap();
+        let err = read_and_convert_image(path.to_str().unwrap(), format, None, DEFAULT_MAX_PIXELS)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("100000x100000"), "{:#}", err);
+
+        // Images within the limit are decoded as usual
+        let small = dir.path().join("small.png");
+        write_test_image(&small, 8, 8);
+        let format = parse_image_format("png", 90).unwrap();
+        assert!(
+            read_and_convert_image(small.to_str().unwrap(), format, None, 64)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_image_name_matches_format() {
+        let png = parse_image_format("png", 90).unwrap();
+        let jpeg = parse_image_format("jpeg", 90).unwrap();
+
+        assert_eq!(image_name("photos/cat.jpg", png.as_ref()), "cat.png");
+        assert_eq!(image_name("photos/cat.png", jpeg.as_ref()), "cat.jpg");
+        assert_eq!(image_name("photos/cat.gif", None), "cat.gif");
+    }
+
+    #[tokio::test]
+    async fn test_run_script_reports_failed_lines_and_continues() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, b"notes").unwrap();
+        let script = format!(
+            "Hello\n.file {}\n.history many\n\n.file {}\n",
+            dir.path().join("missing.txt").display(),
+            path.display()
+        );
+        let options = ClientOptions {
+            capabilities: Capabilities::default(),
+            quiet: true,
+            image_format: None,
+            max_dim: None,
+            max_pixels: DEFAULT_MAX_PIXELS,
         };
 
-        // Serialize and send the message to the server
-        send_message(&mut stream, &message).await?;
+        let stream = Mutex::new(Vec::new());
+        let mut errors = Vec::new();
+        let failed = run_script(script.as_bytes(), &stream, &options, &mut errors)
+            .await
+            .unwrap();
 
-        // If the user wants to quit, break the loop
-        if let MessageType::Quit = message {
-            break;
+        // The failing lines are reported with their numbers
+        assert_eq!(failed, 2);
+        let errors = String::from_utf8(errors).unwrap();
+        let lines: Vec<_> = errors.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Line 2: "), "{}", errors);
+        assert!(lines[1].starts_with("Line 3: "), "{}", errors);
+
+        // The other lines are sent in order, followed by a quit
+        let sent = stream.into_inner();
+        let mut sent = sent.as_slice();
+        match receive_message(&mut sent).await.unwrap() {
+            MessageType::TextWithTime(text, _) => assert_eq!(text, "Hello"),
+            other => panic!("Unexpected message: {:?}", other),
+        }
+        match receive_message(&mut sent).await.unwrap() {
+            MessageType::FileChunk { name, data, .. } => {
+                assert_eq!(
+                    (name.as_str(), data.as_slice()),
+                    ("notes.txt", &b"notes"[..])
+                )
+            }
+            other => panic!("Unexpected message: {:?}", other),
         }
+        assert!(matches!(
+            receive_message(&mut sent).await.unwrap(),
+            MessageType::Quit
+        ));
+        assert!(sent.is_empty());
     }
 
-    Ok(())
+    #[tokio::test]
+    async fn test_send_message_into_vec() {
+        let message = MessageType::Text("Hello, server!".to_string());
+        let mut buffer = Vec::new();
+        send_message(&mut buffer, &message).await.unwrap();
+
+        // A 4-byte big-endian length prefix followed by the serialized message
+        let payload = shared::encode_message(&message).unwrap();
+        assert_eq!(buffer[..4], (payload.len() as u32).to_be_bytes());
+        assert_eq!(buffer[4..], payload);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_reports_refusal() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+
+        // A stub server refusing every version
+        let server = tokio::spawn(async move {
+            let hello = receive_message(&mut server).await.unwrap();
+            let error = MessageType::Text("Error: unsupported protocol version".to_string());
+            send_framed(&mut server, &error).await.unwrap();
+            hello
+        });
+
+        let err = handshake(&mut client, false, false).await.unwrap_err();
+        assert!(
+            err.to_string().contains("unsupported protocol version"),
+            "{}",
+            err
+        );
+        assert!(matches!(
+            server.await.unwrap(),
+            MessageType::Hello {
+                version: PROTOCOL_VERSION,
+                compression: false
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_caps_show_negotiated_capabilities() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+
+        // A stub server granting the compression the client asks for
+        let server = tokio::spawn(async move {
+            let hello = receive_message(&mut server).await.unwrap();
+            let reply = MessageType::Hello {
+                version: PROTOCOL_VERSION,
+                compression: true,
+            };
+            send_framed(&mut server, &reply).await.unwrap();
+            hello
+        });
+
+        let capabilities = handshake(&mut client, true, false).await.unwrap();
+        assert!(matches!(
+            server.await.unwrap(),
+            MessageType::Hello {
+                compression: true,
+                ..
+            }
+        ));
+
+        // The table printed by `.caps`
+        let table = capabilities.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines.contains(&"| compression | on      | "));
+        assert!(lines.contains(&"| encryption  | off     | "));
+        assert!(lines.contains(&format!("| format      | {:<7} | ", shared::WIRE_FORMAT).as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_send_once_waits_for_ack() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // A stub server that pings before acknowledging the text message
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let message = receive_message(&mut stream).await.unwrap();
+            send_framed(&mut stream, &MessageType::Ping).await.unwrap();
+            let pong = receive_message(&mut stream).await.unwrap();
+            send_framed(&mut stream, &MessageType::Ack(7))
+                .await
+                .unwrap();
+            (message, pong)
+        });
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        assert_eq!(send_once(&mut stream, "hello").await.unwrap(), 7);
+
+        match server.await.unwrap() {
+            (MessageType::Text(text), MessageType::Pong) => assert_eq!(text, "hello"),
+            other => panic!("Unexpected messages: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_once_fails_when_server_closes() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            receive_message(&mut stream).await.unwrap();
+        });
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        assert!(send_once(&mut stream, "hello").await.is_err());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_print_incoming_from_stub_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // A stub server that pings, pushes one message and closes the connection once the ping
+        // is answered
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            send_framed(&mut stream, &MessageType::Ping).await.unwrap();
+            let message = MessageType::TextWithTime("bob: Hello, Alice!".to_string(), 0);
+            send_framed(&mut stream, &message).await.unwrap();
+            receive_message(&mut stream).await.unwrap()
+        });
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let (mut reader, writer) = stream.into_split();
+        let mut out = Vec::new();
+        print_incoming(
+            &mut reader,
+            &Mutex::new(writer),
+            &mut out,
+            DEFAULT_DOWNLOAD_DIR,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(server.await.unwrap(), MessageType::Pong));
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "bob: Hello, Alice!\nDisconnected from the server\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_downloaded_file_is_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloads = dir.path().join("downloads");
+        let content = b"Downloaded".to_vec();
+        let file = MessageType::File(
+            "photos/1700000000_notes.txt".to_string(),
+            content.clone(),
+            sha256_hex(&content),
+        );
+        let corrupt = MessageType::File("bad.txt".to_string(), content.clone(), sha256_hex(b"x"));
+
+        let mut input = Vec::new();
+        send_framed(&mut input, &file).await.unwrap();
+        send_framed(&mut input, &corrupt).await.unwrap();
+        let mut out = Vec::new();
+        let writer = Mutex::new(Vec::new());
+        print_incoming(
+            &mut input.as_slice(),
+            &writer,
+            &mut out,
+            downloads.to_str().unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // Only the intact file is saved, under its name without directories
+        let saved: Vec<_> = std::fs::read_dir(&downloads)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0]
+            .to_str()
+            .unwrap()
+            .ends_with("_1700000000_notes.txt"));
+        assert_eq!(std::fs::read(&saved[0]).unwrap(), content);
+    }
 }
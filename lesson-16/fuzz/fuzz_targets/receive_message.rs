@@ -0,0 +1,27 @@
+// fuzz/fuzz_targets/receive_message.rs
+
+//! Feeds arbitrary bytes to `receive_message` as if a client had sent them, framing included.
+//! Any outcome but a panic, or an allocation the size of a bogus length prefix, is fine: the
+//! input is either a valid message or rejected with a `ReceiveError`.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use shared::receive_message;
+use tokio::runtime::{Builder, Runtime};
+
+/// Runtime shared by all runs, building one per input would dominate the time spent.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Builder::new_current_thread().build().unwrap())
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut stream = data;
+    runtime().block_on(async {
+        // Keep reading, a single input may hold several framed messages
+        while receive_message(&mut stream).await.is_ok() {}
+    });
+});
@@ -0,0 +1,7 @@
+-- Text messages sent to the chat
+CREATE TABLE IF NOT EXISTS messages (
+    id SERIAL PRIMARY KEY,
+    "user" TEXT NOT NULL,
+    content TEXT NOT NULL,
+    sent_at BIGINT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Time the server stored each message at, used to order the message history
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now();
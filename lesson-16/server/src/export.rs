@@ -0,0 +1,170 @@
+// server/src/export.rs
+
+//! # CSV Export and Import
+//!
+//! Export of the stored text messages to CSV, built on the `Csv` table of the lesson 7 text
+//! modifiers. Each message becomes a row with the columns `id`, `user`, `content` and
+//! `created_at`, the latter in RFC 3339.
+//!
+//! Messages can be imported from CSV as well, e.g. to seed a demo. Only the `user` and `content`
+//! columns are read, so an exported file can be imported again.
+
+use anyhow::{anyhow, bail, Context, Result};
+use lesson_02::{Csv, TextModifier};
+use shared::unix_time;
+
+use crate::{store::MessageStore, Message};
+
+/// Header row of the exported CSV.
+pub const CSV_HEADERS: [&str; 4] = ["id", "user", "content", "created_at"];
+
+/// Builds a CSV table from messages, one row per message in the given order.
+pub fn messages_to_csv(messages: &[Message]) -> Csv {
+    Csv {
+        headers: CSV_HEADERS
+            .iter()
+            .map(|header| header.to_string())
+            .collect(),
+        rows: messages
+            .iter()
+            .map(|message| {
+                vec![
+                    message.id.to_string(),
+                    message.user.clone(),
+                    message.content.clone(),
+                    message.created_at.to_rfc3339(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Writes all messages of a store to a CSV file, oldest first.
+///
+/// # Arguments
+///
+/// * `store` - The `MessageStore` to read the messages from.
+/// * `path` - The path of the CSV file, which is overwritten if it exists.
+///
+/// # Returns
+///
+/// A `Result` containing the number of exported messages, or an `anyhow::Error` if the messages
+/// can't be loaded or the file can't be written.
+pub async fn export_csv(store: &dyn MessageStore, path: &str) -> Result<usize> {
+    let messages = store.all().await.context("Failed to load the messages")?;
+    let csv = messages_to_csv(&messages)
+        .to_csv_string(b',')
+        .map_err(|err| anyhow!("Failed to serialize the messages: {}", err))?;
+    tokio::fs::write(path, csv)
+        .await
+        .with_context(|| format!("Failed to write {}", path))?;
+    Ok(messages.len())
+}
+
+/// Saves the messages of a CSV file in a store, in the order of its rows.
+///
+/// # Arguments
+///
+/// * `store` - The `MessageStore` to save the messages in.
+/// * `input` - The comma separated CSV, starting with a header row containing `user` and
+///   `content` columns. Other columns are ignored.
+///
+/// # Returns
+///
+/// A `Result` containing the number of imported messages, or an `anyhow::Error` if the CSV is
+/// invalid, misses a column or a message can't be saved. Rows before the failing one are kept.
+pub async fn import_csv(store: &dyn MessageStore, input: &str) -> Result<usize> {
+    let csv = TextModifier::parse_csv_with_delimiter(input, b',')
+        .map_err(|err| anyhow!("Invalid CSV: {}", err))?;
+    let column = |name: &str| match csv.headers.iter().position(|header| header.trim() == name) {
+        Some(column) => Ok(column),
+        None => bail!("The CSV has no '{}' column", name),
+    };
+    let (user, content) = (column("user")?, column("content")?);
+
+    let sent_at = unix_time();
+    for (e, row) in csv.rows.iter().enumerate() {
+        store
+            .save_message(&row[user], &row[content], sent_at)
+            .await
+            .with_context(|| format!("Failed to save the message of row {}", e + 2))?;
+    }
+    Ok(csv.rows.len())
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::store::{open_store, MEMORY_URL};
+
+    #[test]
+    fn test_messages_to_csv_quotes_content() {
+        let messages = vec![Message {
+            id: 1,
+            user: "alice".to_string(),
+            content: "Hi, Bob".to_string(),
+            sent_at: 0,
+            created_at: Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+        }];
+
+        let csv = messages_to_csv(&messages);
+        assert_eq!(csv.headers, CSV_HEADERS);
+        assert_eq!(
+            csv.to_csv_string(b',').unwrap(),
+            "id,user,content,created_at\n1,alice,\"Hi, Bob\",2024-01-02T03:04:05+00:00\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_writes_oldest_first() {
+        let store = open_store(MEMORY_URL).await.unwrap();
+        for content in ["one", "two"] {
+            store.save_message("bob", content, 0).await.unwrap();
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.csv");
+
+        let count = export_csv(store.as_ref(), path.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        let csv = std::fs::read_to_string(path).unwrap();
+        let rows: Vec<&str> = csv.lines().map(|line| &line[..5]).collect();
+        assert_eq!(rows, ["id,us", "1,bob", "2,bob"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_saves_rows() {
+        let store = open_store(MEMORY_URL).await.unwrap();
+        let csv = "user,content\nalice,Hello\nbob,\"Hi, Alice\"\n";
+
+        assert_eq!(import_csv(store.as_ref(), csv).await.unwrap(), 2);
+        let messages: Vec<_> = store
+            .all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|message| (message.user, message.content))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                ("alice".to_string(), "Hello".to_string()),
+                ("bob".to_string(), "Hi, Alice".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_requires_columns() {
+        let store = open_store(MEMORY_URL).await.unwrap();
+        let err = import_csv(store.as_ref(), "user,text\nalice,Hello\n")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "The CSV has no 'content' column");
+        assert!(store.all().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+// server/src/heartbeat.rs
+
+//! # Heartbeat
+//!
+//! Detection of dead peers. A TCP connection can hang without either end noticing, so the server
+//! sends a `MessageType::Ping` to a client that has been silent for an interval and drops the
+//! connection if no message, usually the `MessageType::Pong` answer, arrives within another
+//! interval.
+
+use std::time::{Duration, Instant};
+
+/// What to do about a client after checking its heartbeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeartbeatAction {
+    /// The client was heard from recently enough, nothing to do.
+    Wait,
+    /// The client has been silent for an interval and should be pinged.
+    Ping,
+    /// The client didn't answer the ping in time and should be dropped.
+    Drop,
+}
+
+/// Heartbeat state of a single client.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    interval: Duration,
+    last_seen: Instant,
+    ping_sent: Option<Instant>,
+}
+
+impl Heartbeat {
+    /// Creates the heartbeat of a client last heard from at `now`, pinging it after `interval` of
+    /// silence and giving it another `interval` to answer.
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Heartbeat {
+            interval,
+            last_seen: now,
+            ping_sent: None,
+        }
+    }
+
+    /// Records a message received from the client at `now`.
+    pub fn received(&mut self, now: Instant) {
+        self.last_seen = now;
+        self.ping_sent = None;
+    }
+
+    /// Returns when the heartbeat should be checked next.
+    pub fn next_check(&self) -> Instant {
+        match self.ping_sent {
+            Some(sent) => sent + self.interval,
+            None => self.last_seen + self.interval,
+        }
+    }
+
+    /// Decides what to do about the client at `now`. Returning `HeartbeatAction::Ping` records
+    /// the ping as sent.
+    pub fn check(&mut self, now: Instant) -> HeartbeatAction {
+        match self.ping_sent {
+            Some(sent) if now >= sent + self.interval => HeartbeatAction::Drop,
+            Some(_) => HeartbeatAction::Wait,
+            None if now >= self.last_seen + self.interval => {
+                self.ping_sent = Some(now);
+                HeartbeatAction::Ping
+            }
+            None => HeartbeatAction::Wait,
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn test_silent_client_is_pinged_then_dropped() {
+        let start = Instant::now();
+        let mut heartbeat = Heartbeat::new(INTERVAL, start);
+
+        assert_eq!(heartbeat.check(start + INTERVAL / 2), HeartbeatAction::Wait);
+        assert_eq!(heartbeat.check(start + INTERVAL), HeartbeatAction::Ping);
+        assert_eq!(heartbeat.next_check(), start + INTERVAL * 2);
+
+        // No pong within the window
+        assert_eq!(
+            heartbeat.check(start + INTERVAL * 3 / 2),
+            HeartbeatAction::Wait
+        );
+        assert_eq!(heartbeat.check(start + INTERVAL * 2), HeartbeatAction::Drop);
+    }
+
+    #[test]
+    fn test_pong_in_time_keeps_client() {
+        let start = Instant::now();
+        let mut heartbeat = Heartbeat::new(INTERVAL, start);
+        assert_eq!(heartbeat.check(start + INTERVAL), HeartbeatAction::Ping);
+
+        // The pong resets the heartbeat, so the client is only pinged again after an interval
+        let pong = start + INTERVAL + Duration::from_secs(1);
+        heartbeat.received(pong);
+        assert_eq!(heartbeat.check(start + INTERVAL * 2), HeartbeatAction::Wait);
+        assert_eq!(heartbeat.next_check(), pong + INTERVAL);
+    }
+}
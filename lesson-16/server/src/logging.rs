@@ -0,0 +1,79 @@
+// server/src/logging.rs
+
+//! # Logging
+//!
+//! Setup of the subscriber that writes the server logs. Logs are human-readable text by default,
+//! or one JSON object per line, including the fields of the enclosing spans, for shipping them to
+//! log aggregation.
+
+use anyhow::{anyhow, bail, Result};
+use tracing::Subscriber;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Format the server logs are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+impl LogFormat {
+    /// Names of the formats accepted by `LogFormat::parse`.
+    pub const NAMES: [&'static str; 2] = ["text", "json"];
+
+    /// Parses a log format from its name, `text` or `json`.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("Unsupported log format: {}", name),
+        }
+    }
+}
+
+/// Builds the subscriber writing logs to stdout in the given format.
+pub fn build_subscriber(format: LogFormat) -> Box<dyn Subscriber + Send + Sync> {
+    let builder = tracing_subscriber::fmt();
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.json().finish()),
+    }
+}
+
+/// Installs the subscriber for the given format as the global default. Records of the `log`
+/// macros are forwarded to it as well.
+///
+/// # Returns
+///
+/// A `Result` indicating success, or an `anyhow::Error` if a global subscriber is already set.
+pub fn init(format: LogFormat) -> Result<()> {
+    build_subscriber(format)
+        .try_init()
+        .map_err(|err| anyhow!("Failed to initialize logging: {}", err))
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_subscriber_for_each_format() {
+        for name in LogFormat::NAMES {
+            let subscriber = build_subscriber(LogFormat::parse(name).unwrap());
+            tracing::subscriber::with_default(subscriber, || {
+                let span = tracing::info_span!("session", peer = "127.0.0.1:1234");
+                let _entered = span.enter();
+                tracing::info!("Logged in {} format", name);
+            });
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_format() {
+        assert!(LogFormat::parse("xml").is_err());
+    }
+}
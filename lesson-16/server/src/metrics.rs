@@ -0,0 +1,113 @@
+// server/src/metrics.rs
+
+//! # Metrics
+//!
+//! Counts of the messages handled by the server, by type. The counters are atomics shared by all
+//! connections, and a summary of them is logged periodically and when the server shuts down, which
+//! gives some visibility into the traffic without a full monitoring setup.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use log::info;
+use shared::MessageType;
+
+/// Counters of the handled messages, by type.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Received files, counting a chunked file once its last chunk arrives.
+    files: AtomicU64,
+    images: AtomicU64,
+    /// Text messages, with or without a time.
+    texts: AtomicU64,
+    quits: AtomicU64,
+    /// All other messages, e.g. logins, pings and file listings.
+    other: AtomicU64,
+}
+
+impl Metrics {
+    /// Counts a message handled for a client.
+    pub fn record(&self, message: &MessageType) {
+        let counter = match message {
+            MessageType::File(..)
+            | MessageType::CompressedFile(..)
+            | MessageType::FileChunk { last: true, .. } => &self.files,
+            MessageType::FileChunk { .. } => return,
+            MessageType::Image(..) => &self.images,
+            MessageType::Text(_) | MessageType::TextWithTime(..) => &self.texts,
+            MessageType::Quit => &self.quits,
+            _ => &self.other,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for Metrics {
+    /// Renders the counters as a single line, e.g. `files=1 images=0 texts=3 quits=1 other=2`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        write!(
+            f,
+            "files={} images={} texts={} quits={} other={}",
+            load(&self.files),
+            load(&self.images),
+            load(&self.texts),
+            load(&self.quits),
+            load(&self.other)
+        )
+    }
+}
+
+/// Logs a summary of `metrics` every `period`, until the task running it is aborted.
+pub async fn report_every(metrics: Arc<Metrics>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    // The first tick completes right away, before anything was handled
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        info!("Handled messages: {}", metrics);
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_increments_counters() {
+        let metrics = Metrics::default();
+        let chunk = |last| MessageType::FileChunk {
+            name: "notes.txt".to_string(),
+            seq: 0,
+            last,
+            data: Vec::new(),
+            checksum: None,
+            compressed: false,
+        };
+
+        for message in [
+            MessageType::Text("hello".to_string()),
+            MessageType::TextWithTime("hello".to_string(), 0),
+            MessageType::File("a.txt".to_string(), Vec::new(), String::new()),
+            chunk(false),
+            chunk(true),
+            MessageType::Image("cat.png".to_string(), Vec::new()),
+            MessageType::Ping,
+            MessageType::Quit,
+        ] {
+            metrics.record(&message);
+        }
+
+        assert_eq!(
+            metrics.to_string(),
+            "files=2 images=1 texts=2 quits=1 other=1"
+        );
+    }
+}
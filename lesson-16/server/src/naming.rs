@@ -0,0 +1,122 @@
+// server/src/naming.rs
+
+//! # File Naming
+//!
+//! Schemes for naming received files. The original file name is kept as a suffix behind a prefix
+//! that makes it unique: a random UUID by default, or the time the file was received at.
+//! Timestamps only have a resolution of a second, so two files with the same name received in the
+//! same second collide.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Scheme of the prefix put in front of the names of received files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileNaming {
+    /// A random UUIDv4, e.g. `3f2b8c1e-..._cat.jpg`.
+    #[default]
+    Uuid,
+    /// The time the file was received at, in the given format.
+    Timestamp(TimestampFormat),
+}
+
+impl FileNaming {
+    /// Names of the schemes accepted by `FileNaming::parse`.
+    pub const NAMES: [&'static str; 2] = ["uuid", "timestamp"];
+
+    /// Parses a naming scheme from its name, `uuid` or `timestamp`. Timestamps are formatted as
+    /// `TimestampFormat::default()`.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "uuid" => Ok(FileNaming::Uuid),
+            "timestamp" => Ok(FileNaming::Timestamp(TimestampFormat::default())),
+            _ => bail!("Unsupported file naming: {}", name),
+        }
+    }
+
+    /// Names a received file by putting a new prefix of this scheme in front of `filename`.
+    pub fn file_name(self, filename: &str) -> String {
+        let prefix = match self {
+            FileNaming::Uuid => Uuid::new_v4().to_string(),
+            FileNaming::Timestamp(format) => format.format(Utc::now()),
+        };
+        format!("{}_{}", prefix, filename)
+    }
+}
+
+/// Format of the timestamps received files are named with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+    /// The unix time in seconds, e.g. `1700000000_cat.jpg`.
+    #[default]
+    Epoch,
+    /// The UTC date and time, e.g. `20231114-221320_cat.jpg`.
+    Human,
+}
+
+impl TimestampFormat {
+    /// Names of the formats accepted by `TimestampFormat::parse`.
+    pub const NAMES: [&'static str; 2] = ["epoch", "human"];
+
+    /// Parses a timestamp format from its name, `epoch` or `human`.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "epoch" => Ok(TimestampFormat::Epoch),
+            "human" => Ok(TimestampFormat::Human),
+            _ => bail!("Unsupported timestamp format: {}", name),
+        }
+    }
+
+    /// Formats `time` in this format.
+    pub fn format(self, time: DateTime<Utc>) -> String {
+        match self {
+            TimestampFormat::Epoch => time.timestamp().to_string(),
+            TimestampFormat::Human => time.format("%Y%m%d-%H%M%S").to_string(),
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_keeps_original_name_as_suffix() {
+        let name = FileNaming::Uuid.file_name("cat.jpg");
+        let (uuid, rest) = name.split_once('_').unwrap();
+        assert!(Uuid::parse_str(uuid).is_ok());
+        assert_eq!(rest, "cat.jpg");
+    }
+
+    #[test]
+    fn test_timestamp_prefix_parses_in_both_formats() {
+        let before = Utc::now().timestamp();
+
+        let name = FileNaming::Timestamp(TimestampFormat::Epoch).file_name("cat.jpg");
+        let (prefix, rest) = name.split_once('_').unwrap();
+        let epoch: i64 = prefix.parse().unwrap();
+        assert!(epoch >= before);
+        assert_eq!(rest, "cat.jpg");
+
+        let name = FileNaming::Timestamp(TimestampFormat::Human).file_name("cat.jpg");
+        let (prefix, rest) = name.split_once('_').unwrap();
+        let human = chrono::NaiveDateTime::parse_from_str(prefix, "%Y%m%d-%H%M%S").unwrap();
+        assert!(human.and_utc().timestamp() >= before);
+        assert_eq!(rest, "cat.jpg");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_scheme() {
+        assert_eq!(
+            FileNaming::parse("timestamp").unwrap(),
+            FileNaming::Timestamp(TimestampFormat::Epoch)
+        );
+        assert_eq!(
+            TimestampFormat::parse("human").unwrap(),
+            TimestampFormat::Human
+        );
+        assert!(FileNaming::parse("sequence").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+// server/src/proxy.rs
+
+//! # PROXY Protocol
+//!
+//! Support for the PROXY protocol v1 header that load balancers prepend to a forwarded connection,
+//! e.g. `PROXY TCP4 192.0.2.1 198.51.100.1 56324 11111\r\n`. The header carries the address of the
+//! real client, which would otherwise be hidden behind the address of the proxy.
+
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::{bail, Context, Result};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Maximum length of a PROXY v1 header line including the trailing CRLF, as defined by the spec.
+const MAX_HEADER_LEN: usize = 107;
+
+/// Reads the PROXY v1 header line from the start of a connection.
+///
+/// The header is read byte by byte so no data following it is consumed from the stream.
+///
+/// # Arguments
+///
+/// * `reader` - The stream of the accepted connection.
+///
+/// # Returns
+///
+/// The source address announced in the header, `None` for a `PROXY UNKNOWN` header, or an
+/// `anyhow::Error` if the header is malformed.
+pub async fn read_proxy_header<R>(reader: &mut R) -> Result<Option<SocketAddr>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut line = Vec::with_capacity(MAX_HEADER_LEN);
+
+    while !line.ends_with(b"\r\n") {
+        if line.len() == MAX_HEADER_LEN {
+            bail!("PROXY header exceeds {} bytes", MAX_HEADER_LEN);
+        }
+        let byte = reader
+            .read_u8()
+            .await
+            .context("Connection closed before the PROXY header ended")?;
+        line.push(byte);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2]).context("PROXY header is not ASCII")?;
+    parse_proxy_header(line)
+}
+
+/// Parses a PROXY v1 header line without its trailing CRLF.
+fn parse_proxy_header(line: &str) -> Result<Option<SocketAddr>> {
+    let parts: Vec<&str> = line.split(' ').collect();
+
+    match parts.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", protocol @ ("TCP4" | "TCP6"), source, _destination, source_port, _destination_port] =>
+        {
+            let ip: IpAddr = source
+                .parse()
+                .with_context(|| format!("Invalid source address in PROXY header: {}", line))?;
+            if ip.is_ipv4() != (*protocol == "TCP4") {
+                bail!("Source address does not match {} in PROXY header", protocol);
+            }
+            let port: u16 = source_port
+                .parse()
+                .with_context(|| format!("Invalid source port in PROXY header: {}", line))?;
+
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => bail!("Malformed PROXY header: {}", line),
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_proxy_header_tcp4() {
+        let mut stream = tokio_test::io::Builder::new()
+            .read(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 11111\r\n")
+            .build();
+
+        let addr = read_proxy_header(&mut stream).await.unwrap();
+        assert_eq!(addr, Some("192.0.2.1:56324".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_read_proxy_header_rejects_malformed() {
+        let mut stream = tokio_test::io::Builder::new()
+            .read(b"GET / HTTP/1.1\r\n")
+            .build();
+
+        assert!(read_proxy_header(&mut stream).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_proxy_header_unknown_and_mismatch() {
+        assert_eq!(parse_proxy_header("PROXY UNKNOWN").unwrap(), None);
+        assert!(parse_proxy_header("PROXY TCP4 ::1 ::1 1 2").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+// server/src/rate_limit.rs
+
+//! # Rate Limiting
+//!
+//! A token bucket capping the number of messages a client may send per second. The bucket holds
+//! up to one second worth of tokens, so a client may send a short burst after being quiet, and
+//! refills continuously at the configured rate.
+
+use std::time::Instant;
+
+/// Token bucket allowing `rate` messages per second on average.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket allowing `rate` messages per second.
+    pub fn per_second(rate: u32) -> Self {
+        Self::new(rate, Instant::now())
+    }
+
+    /// Creates a full bucket allowing `rate` messages per second, starting at `now`.
+    fn new(rate: u32, now: Instant) -> Self {
+        let rate = f64::from(rate);
+        TokenBucket {
+            rate,
+            capacity: rate,
+            tokens: rate,
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token for a message, returning `false` if the bucket is empty and the message
+    /// should be dropped.
+    pub fn try_acquire(&mut self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    /// Takes a token for a message received at `now`.
+    fn try_acquire_at(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_burst_up_to_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(3, start);
+
+        assert!((0..3).all(|_| bucket.try_acquire_at(start)));
+        assert!(!bucket.try_acquire_at(start));
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, start);
+        while bucket.try_acquire_at(start) {}
+
+        // Half a second refills one of the two tokens per second
+        let later = start + Duration::from_millis(500);
+        assert!(bucket.try_acquire_at(later));
+        assert!(!bucket.try_acquire_at(later));
+
+        // A long pause refills the bucket only up to its capacity
+        let much_later = later + Duration::from_secs(60);
+        assert!((0..2).all(|_| bucket.try_acquire_at(much_later)));
+        assert!(!bucket.try_acquire_at(much_later));
+    }
+
+    #[test]
+    fn test_zero_rate_drops_everything() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(0, start);
+
+        assert!(!bucket.try_acquire_at(start + Duration::from_secs(10)));
+    }
+}
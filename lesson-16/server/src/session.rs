@@ -0,0 +1,131 @@
+// server/src/session.rs
+
+//! # Client Sessions
+//!
+//! State that belongs to a single client connection, such as the write half used for replies and
+//! a chunked file transfer that is still in progress.
+
+use std::net::SocketAddr;
+
+use anyhow::{bail, Context, Result};
+use log::warn;
+use sha2::{Digest, Sha256};
+use tokio::{fs::File, io::AsyncWriteExt};
+
+use crate::{heartbeat::Heartbeat, rate_limit::TokenBucket, ClientWriter};
+
+/// File extension of a file that is still being written. Received files are written under this
+/// extension and only renamed to their final name once complete.
+pub const PARTIAL_EXTENSION: &str = "part";
+
+/// Returns the path a file is written at before it is moved to `path`.
+pub fn partial_path(path: &str) -> String {
+    format!("{}.{}", path, PARTIAL_EXTENSION)
+}
+
+/// Structure representing the state of a connected client.
+pub struct Session {
+    /// Address of the client.
+    pub peer: SocketAddr,
+    /// Write half of the client connection, used for replies.
+    pub writer: ClientWriter,
+    /// Chunked file transfer that hasn't received its final chunk yet.
+    pub transfer: Option<FileTransfer>,
+    /// Limit on the messages per second the client may send, if any.
+    pub rate_limit: Option<TokenBucket>,
+    /// Heartbeat of the client, if silent clients are pinged.
+    pub heartbeat: Option<Heartbeat>,
+    /// Buffer the messages of the client are read into, reused for every message.
+    pub buffer: Vec<u8>,
+}
+
+impl Session {
+    /// Creates the session of a newly connected client.
+    pub fn new(peer: SocketAddr, writer: ClientWriter) -> Self {
+        Session {
+            peer,
+            writer,
+            transfer: None,
+            rate_limit: None,
+            heartbeat: None,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// Structure representing a chunked file transfer. Chunks are written to the partial file of the
+/// target path as they arrive and must come in sequence. The SHA-256 hash of the content is
+/// computed along the way, so the file never has to be read back.
+pub struct FileTransfer {
+    name: String,
+    path: String,
+    partial: String,
+    next_seq: u64,
+    file: File,
+    hasher: Sha256,
+}
+
+impl FileTransfer {
+    /// Starts a transfer of the file `name` to `path` by creating its partial file.
+    pub async fn start(name: &str, path: String) -> Result<Self> {
+        let partial = partial_path(&path);
+        let file = File::create(&partial)
+            .await
+            .with_context(|| format!("Failed to create file at {}", partial))?;
+
+        Ok(FileTransfer {
+            name: name.to_string(),
+            path,
+            partial,
+            next_seq: 0,
+            file,
+            hasher: Sha256::new(),
+        })
+    }
+
+    /// Returns the name of the transferred file, as sent by the client.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Appends the data of chunk `seq` to the target file. Chunks arriving out of order are
+    /// rejected with an error.
+    pub async fn write_chunk(&mut self, seq: u64, data: &[u8]) -> Result<()> {
+        if seq != self.next_seq {
+            bail!(
+                "Out-of-order chunk {} of {}, expected chunk {}",
+                seq,
+                self.name,
+                self.next_seq
+            );
+        }
+
+        self.file
+            .write_all(data)
+            .await
+            .with_context(|| format!("Failed to write content to file at {}", self.partial))?;
+        self.hasher.update(data);
+        self.next_seq += 1;
+
+        Ok(())
+    }
+
+    /// Completes the transfer and returns the target path and the hex-encoded SHA-256 hash of
+    /// the written content. The written file stays at the partial path until it is moved into
+    /// place.
+    pub async fn finish(mut self) -> Result<(String, String)> {
+        self.file
+            .flush()
+            .await
+            .with_context(|| format!("Failed to write content to file at {}", self.partial))?;
+        Ok((self.path, format!("{:x}", self.hasher.finalize())))
+    }
+
+    /// Abandons the transfer and removes the partially written file.
+    pub async fn abort(self) {
+        drop(self.file);
+        if let Err(err) = tokio::fs::remove_file(&self.partial).await {
+            warn!("Failed to remove partial file {}: {}", self.partial, err);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+// server/src/store.rs
+
+//! # Message Stores
+//!
+//! Backends the server stores text messages in. PostgreSQL (feature `postgres`, enabled by
+//! default) and SQLite (feature `sqlite`) keep messages in a database, while the in-memory store
+//! keeps them only for the lifetime of the server, which is handy for local runs and tests.
+//!
+//! The backend is chosen by the scheme of the database URL, see `open_store`.
+
+use std::{fmt::Debug, sync::Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use sqlx::Error as SqlxError;
+
+use crate::Message;
+
+/// URL selecting the in-memory store.
+pub const MEMORY_URL: &str = "memory";
+
+/// Storage of the text messages sent to the chat.
+#[async_trait]
+pub trait MessageStore: Debug + Send + Sync {
+    /// Saves a message and returns its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - A string representing the username associated with the message.
+    /// * `content` - A string containing the content of the message.
+    /// * `sent_at` - The unix time (in seconds) the message was sent at.
+    async fn save_message(&self, user: &str, content: &str, sent_at: u64)
+        -> Result<i32, SqlxError>;
+
+    /// Loads up to `limit` of the most recent messages, newest first.
+    async fn recent(&self, limit: i64) -> Result<Vec<Message>, SqlxError>;
+
+    /// Loads all messages, oldest first.
+    async fn all(&self) -> Result<Vec<Message>, SqlxError> {
+        let mut messages = self.recent(i64::MAX).await?;
+        messages.reverse();
+        Ok(messages)
+    }
+}
+
+/// Opens the message store for `url`: `memory` for the in-memory store, `sqlite:` URLs for
+/// SQLite and `postgres://`/`postgresql://` URLs for PostgreSQL.
+///
+/// # Arguments
+///
+/// * `url` - The database URL.
+///
+/// # Returns
+///
+/// A `Result` containing the store, or an `anyhow::Error` if the URL selects a backend that isn't
+/// compiled in or the database can't be opened.
+pub async fn open_store(url: &str) -> Result<Arc<dyn MessageStore>> {
+    if url == MEMORY_URL {
+        return Ok(Arc::new(MemoryStore::default()));
+    }
+    #[cfg(feature = "sqlite")]
+    if url.starts_with("sqlite:") {
+        return Ok(Arc::new(SqliteStore::new(url).await?));
+    }
+    #[cfg(feature = "postgres")]
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        return Ok(Arc::new(crate::Database::new(url).await?));
+    }
+
+    bail!("Unsupported database URL: {}", url)
+}
+
+/// Message store keeping messages in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    messages: std::sync::Mutex<Vec<Message>>,
+}
+
+#[async_trait]
+impl MessageStore for MemoryStore {
+    async fn save_message(
+        &self,
+        user: &str,
+        content: &str,
+        sent_at: u64,
+    ) -> Result<i32, SqlxError> {
+        let sent_at = i64::try_from(sent_at)
+            .map_err(|_| SqlxError::Protocol(format!("Timestamp out of range: {}", sent_at)))?;
+
+        let mut messages = self.messages.lock().unwrap();
+        let id = messages.len() as i32 + 1;
+        messages.push(Message {
+            id,
+            user: user.to_string(),
+            content: content.to_string(),
+            sent_at,
+            created_at: chrono::Utc::now(),
+        });
+        Ok(id)
+    }
+
+    async fn recent(&self, limit: i64) -> Result<Vec<Message>, SqlxError> {
+        let messages = self.messages.lock().unwrap();
+        let limit = usize::try_from(limit).unwrap_or(0);
+        Ok(messages.iter().rev().take(limit).cloned().collect())
+    }
+}
+
+/// Message store keeping messages in a SQLite database.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub struct SqliteStore {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Opens the SQLite database at `url`, creating it and its messages table if needed.
+    pub async fn new(url: &str) -> Result<Self, SqlxError> {
+        use std::str::FromStr;
+
+        let options = sqlx::sqlite::SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(options).await?;
+        sqlx::query(
+            r#"CREATE TABLE IF NOT EXISTS messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                "user" TEXT NOT NULL,
+                content TEXT NOT NULL,
+                sent_at BIGINT NOT NULL,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"#,
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(SqliteStore { pool })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl MessageStore for SqliteStore {
+    async fn save_message(
+        &self,
+        user: &str,
+        content: &str,
+        sent_at: u64,
+    ) -> Result<i32, SqlxError> {
+        let sent_at = i64::try_from(sent_at)
+            .map_err(|_| SqlxError::Protocol(format!("Timestamp out of range: {}", sent_at)))?;
+
+        // Executed to completion rather than fetching a RETURNING row, which leaves the insert
+        // uncommitted until the statement is reset, hidden from the other pooled connections
+        let id =
+            sqlx::query(r#"INSERT INTO messages ("user", content, sent_at) VALUES ($1, $2, $3)"#)
+                .bind(user)
+                .bind(content)
+                .bind(sent_at)
+                .execute(&self.pool)
+                .await?
+                .last_insert_rowid();
+        i32::try_from(id)
+            .map_err(|_| SqlxError::Protocol(format!("Message id out of range: {}", id)))
+    }
+
+    async fn recent(&self, limit: i64) -> Result<Vec<Message>, SqlxError> {
+        sqlx::query_as(
+            r#"SELECT id, "user", content, sent_at, created_at FROM messages
+            ORDER BY created_at DESC, id DESC LIMIT $1"#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store_recent_newest_first() {
+        let store = open_store(MEMORY_URL).await.unwrap();
+        for content in ["one", "two", "three"] {
+            store.save_message("alice", content, 0).await.unwrap();
+        }
+
+        let recent = store.recent(2).await.unwrap();
+        assert!(recent[0].created_at >= recent[1].created_at);
+        let recent: Vec<_> = recent.iter().map(|m| (m.id, m.content.as_str())).collect();
+        assert_eq!(recent, vec![(3, "three"), (2, "two")]);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", dir.path().join("chat.db").display());
+        let store = open_store(&url).await.unwrap();
+        let id = store.save_message("bob", "Hello", 7).await.unwrap();
+
+        let recent = store.recent(10).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!((recent[0].id, recent[0].sent_at), (id, 7));
+    }
+
+    #[tokio::test]
+    async fn test_open_store_rejects_unknown_url() {
+        assert!(open_store("mysql://localhost/chat").await.is_err());
+    }
+}
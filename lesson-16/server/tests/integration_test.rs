@@ -0,0 +1,95 @@
+// server/tests/integration_test.rs
+
+//! Integration tests running the server binary and talking to it over TCP like a client.
+
+use std::{
+    fs,
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+use shared::{receive_message, send_framed, sha256_hex, MessageType, PROTOCOL_VERSION};
+use tokio::{net::TcpStream, time::sleep};
+
+/// Server process killed when the test ends, also when it fails.
+struct ServerProcess(Child);
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Starts the server binary on a free local port, keeping messages in memory and storing files
+/// under `files_dir`.
+fn start_server(files_dir: &str, images_dir: &str) -> (ServerProcess, SocketAddr) {
+    // Let the OS pick a free port, then release it for the server
+    let addr = StdTcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let child = Command::new(env!("CARGO_BIN_EXE_server"))
+        .args(["--bind-address", &addr.to_string()])
+        .args(["--files-dir", files_dir, "--images-dir", images_dir])
+        .env("DATABASE_URL", "memory")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start the server binary");
+    (ServerProcess(child), addr)
+}
+
+/// Connects to the server, retrying while it is still starting up.
+async fn connect(addr: SocketAddr) -> TcpStream {
+    for _ in 0..50 {
+        if let Ok(stream) = TcpStream::connect(addr).await {
+            return stream;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    panic!("server did not start listening on {}", addr);
+}
+
+#[tokio::test]
+async fn test_receive_file_integration() {
+    let dir = tempfile::tempdir().unwrap();
+    let files_dir = dir.path().join("files");
+    let images_dir = dir.path().join("images");
+    let (_server, addr) = start_server(files_dir.to_str().unwrap(), images_dir.to_str().unwrap());
+
+    let mut stream = connect(addr).await;
+    let hello = MessageType::Hello {
+        version: PROTOCOL_VERSION,
+        compression: false,
+    };
+    send_framed(&mut stream, &hello).await.unwrap();
+    assert!(matches!(
+        receive_message(&mut stream).await.unwrap(),
+        MessageType::Hello { .. }
+    ));
+
+    // Send a file and wait for the server to acknowledge it
+    let content = b"Test content".to_vec();
+    let file = MessageType::File(
+        "test.txt".to_string(),
+        content.clone(),
+        sha256_hex(&content),
+    );
+    send_framed(&mut stream, &file).await.unwrap();
+    assert!(matches!(
+        receive_message(&mut stream).await.unwrap(),
+        MessageType::Ack(_)
+    ));
+
+    // The file is stored under a unique prefix in front of its original name
+    let stored: Vec<_> = fs::read_dir(&files_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_str().unwrap().ends_with("_test.txt"))
+        .collect();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(fs::read(&stored[0]).unwrap(), content);
+}
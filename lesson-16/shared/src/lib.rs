@@ -1,44 +1,351 @@
 // shared/lib.rs
 use std::{
     error::Error,
+    fmt,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
     time::SystemTime,
 };
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use lesson_02::Csv;
 use log::{error, info}; // Added logging
 use serde_derive::{Deserialize, Serialize}; // Added anyhow
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub mod tls;
 
 /// # Message Types
 ///
 /// This module defines an enumeration `MessageType` representing various types of messages that
 /// can be exchanged between the client and server. These include messages for sending files,
 /// images, plain text, and a Quit signal.
+///
+/// `Ack` is sent by the server once a file, image or text message has been handled. Its id comes
+/// from a server-wide counter that increases with every acknowledged message.
+///
+/// `File` carries the file name, its content and the SHA-256 checksum of the content (see
+/// `sha256_hex`), which the receiver recomputes to catch truncated or corrupted transfers.
+///
+/// `CompressedFile` is a file whose content is gzip-compressed; it is decompressed by the
+/// receiver. Its checksum is the one of the uncompressed content. Plain `File` stays available for
+/// content that is already compressed.
+///
+/// `FileChunk` is one piece of a file sent in chunks, so large files never have to be held in
+/// memory at once. Chunks are numbered from 0 by `seq`, and `last` marks the final one, which also
+/// carries the checksum of the whole file. When `compressed` is set, the data of the chunk is
+/// gzip-compressed on its own; the checksum is still the one of the uncompressed file.
+///
+/// `Image` carries the file name of the image, with the extension of the format it is encoded
+/// in, and its content.
+///
+/// `TextWithTime` is a text message together with the unix time (in seconds) it was sent at, so
+/// the server can keep text messages in order.
+///
+/// `Login` carries the username of the client and must be the first message it sends.
+///
+/// `Verify` is an admin request (gated by the server's admin token) asking the server to check
+/// every stored file against its checksum sidecar; the server answers with `VerifyReport`.
+///
+/// `History` asks the server for the given number of most recent stored text messages, which it
+/// sends back oldest first as `TextWithTime` messages. The server caps how many it sends.
+///
+/// `Hello` is the first message of a connection. The client sends the `PROTOCOL_VERSION` it was
+/// built with, and the server answers with its own `Hello` if it speaks the same version, or with
+/// an error `Text` before closing the connection otherwise, so mismatched builds never
+/// misinterpret each other's messages. The client also asks whether it may compress files, and
+/// the reply tells whether compression is used for the connection.
+///
+/// `Ping` checks that the other end is still there; it is answered with `Pong`. The server pings
+/// clients that have been silent for a while and drops those that don't answer.
+///
+/// `ListFiles` asks the server for the names of the files saved in its files directory, which it
+/// sends back in a `FileList`, relative to that directory. `GetFile` downloads one of them by that
+/// name; the server answers with a `File`, or with an error `Text` if there is no such file.
 #[derive(Serialize, Deserialize, Debug)]
 pub enum MessageType {
-    File(String, Vec<u8>),
-    Image(Vec<u8>),
+    File(String, Vec<u8>, String),
+    CompressedFile(String, Vec<u8>, String),
+    FileChunk {
+        name: String,
+        seq: u64,
+        last: bool,
+        data: Vec<u8>,
+        checksum: Option<String>,
+        compressed: bool,
+    },
+    Image(String, Vec<u8>),
     Text(String),
+    TextWithTime(String, u64),
+    Login(String),
+    Ack(u64),
+    Verify {
+        token: String,
+    },
+    VerifyReport {
+        ok: usize,
+        corrupt: Vec<String>,
+        unverified: Vec<String>,
+    },
+    History(u32),
+    Ping,
+    Pong,
+    Hello {
+        version: u16,
+        compression: bool,
+    },
     Quit,
+    ListFiles,
+    FileList(Vec<String>),
+    GetFile(String),
+}
+
+/// Version of the wire protocol spoken by this build of the client and server.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// # Capabilities
+///
+/// The capability set negotiated for a connection. Both ends keep one per connection so it can
+/// be logged by the server and inspected with the client's `.caps` command.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub version: u16,
+    pub compression: bool,
+    pub encryption: bool,
+    pub endianness: String,
+    pub format: String,
+}
+
+impl Capabilities {
+    /// The capabilities of a connection in the selected `message_format`.
+    ///
+    /// # Arguments
+    ///
+    /// * `compression` - Whether files are compressed, as agreed in the `MessageType::Hello`.
+    /// * `encryption` - Whether the connection is wrapped in TLS.
+    pub fn negotiated(compression: bool, encryption: bool) -> Self {
+        let format = message_format();
+        Capabilities {
+            version: PROTOCOL_VERSION,
+            compression,
+            encryption,
+            endianness: format.endianness().to_string(),
+            format: format.name().to_string(),
+        }
+    }
+}
+
+impl Default for Capabilities {
+    /// The capabilities of a plain connection in the selected `message_format`, without
+    /// compression or encryption.
+    fn default() -> Self {
+        Capabilities::negotiated(false, false)
+    }
+}
+
+impl fmt::Display for Capabilities {
+    /// Renders the capabilities as a two-column table with the lesson-07 CSV table output.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" }.to_string();
+        let table = Csv {
+            headers: vec!["capability".to_string(), "value".to_string()],
+            rows: vec![
+                vec!["version".to_string(), self.version.to_string()],
+                vec!["compression".to_string(), on_off(self.compression)],
+                vec!["encryption".to_string(), on_off(self.encryption)],
+                vec!["endianness".to_string(), self.endianness.clone()],
+                vec!["format".to_string(), self.format.clone()],
+            ],
+        };
+        write!(f, "{}", table)
+    }
+}
+
+/// Default size of the data carried by a single `MessageType::FileChunk` (64 KiB).
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Default upper bound of the length announced by a message frame (64 MiB). Longer messages are
+/// rejected by `receive_message` before any memory is allocated for them; large files are sent in
+/// chunks instead.
+pub const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// File extension of the checksum sidecar stored next to each saved file.
+pub const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// Port the server listens on unless configured otherwise.
+pub const DEFAULT_PORT: u16 = 11111;
+
+/// # Socket Address
+///
+/// This function joins a host and a port into an address for `TcpStream::connect` or
+/// `TcpListener::bind`. IPv6 literals, recognized by the colons in them, are wrapped in brackets,
+/// so `::1` and port 11111 give `[::1]:11111`.
+///
+/// # Arguments
+///
+/// * `host` - A hostname, an IPv4 address or an IPv6 address, with or without brackets.
+/// * `port` - The port number.
+///
+/// # Returns
+///
+/// The address in the form `host:port`.
+pub fn socket_address(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
 }
 
 /// # Send File
 ///
 /// This asynchronous function sends a file to the server over a TCP stream. The file is specified
-/// by its path, and the function reads the file content and sends it with the filename as a
-/// `MessageType::File` variant.
+/// by its path and streamed in `MessageType::FileChunk` messages of `DEFAULT_CHUNK_SIZE` bytes.
+/// When compression is requested, each chunk is compressed on its own.
 ///
 /// # Arguments
 ///
-/// * `stream` - A mutable reference to a `TcpStream` representing the communication channel with
-///              the server.
-/// * `path`   - A string slice representing the path to the file to be sent.
+/// * `stream`   - A mutable reference to the stream representing the communication channel with
+///   the server, e.g. a `TcpStream` or its write half.
+/// * `path`     - A string slice representing the path to the file to be sent.
+/// * `compress` - Whether the file content is gzip-compressed before sending.
+/// * `on_progress` - Called with the bytes sent so far and the total file size as the file is
+///   written to the stream.
 ///
 /// # Returns
 ///
 /// A `Result` indicating success or an `anyhow::Error` if an error occurs during the process.
-pub async fn send_file(stream: &mut TcpStream, path: &str) -> Result<(), anyhow::Error> {
+pub async fn send_file<W, F>(
+    stream: &mut W,
+    path: &str,
+    compress: bool,
+    on_progress: F,
+) -> Result<(), anyhow::Error>
+where
+    W: AsyncWrite + Unpin,
+    F: FnMut(u64, u64),
+{
+    send_file_chunked(stream, path, DEFAULT_CHUNK_SIZE, compress, on_progress).await
+}
+
+/// # Send File in Chunks
+///
+/// This asynchronous function streams the file at `path` as a sequence of `MessageType::FileChunk`
+/// messages carrying the file name and at most `chunk_size` bytes each. Only one chunk is held in
+/// memory at a time.
+/// An empty file is sent as a single empty final chunk.
+///
+/// # Arguments
+///
+/// * `stream`      - A mutable reference to the stream representing the communication channel.
+/// * `path`        - A string slice representing the path to the file to be sent.
+/// * `chunk_size`  - The maximum number of file bytes per chunk.
+/// * `compress`    - Whether the data of each chunk is gzip-compressed before sending.
+/// * `on_progress` - Called after each chunk is written with the bytes sent so far and the total
+///   file size.
+///
+/// # Returns
+///
+/// A `Result` indicating success or an `anyhow::Error` if an error occurs during the process.
+pub async fn send_file_chunked<W, F>(
+    stream: &mut W,
+    path: &str,
+    chunk_size: usize,
+    compress: bool,
+    mut on_progress: F,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    F: FnMut(u64, u64),
+{
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Failed to open file: {}", path))?;
+    let total = file
+        .metadata()
+        .await
+        .with_context(|| format!("Failed to read metadata of file: {}", path))?
+        .len();
+    let mut sent = 0;
+
+    let name = file_name(path)?;
+
+    // Read one chunk ahead, so the final chunk can be marked as such
+    let mut data = read_chunk(&mut file, chunk_size, path).await?;
+    let mut seq = 0;
+    let mut hasher = Sha256::new();
+
+    loop {
+        let next = read_chunk(&mut file, chunk_size, path).await?;
+        let last = next.is_empty();
+        sent += data.len() as u64;
+        hasher.update(&data);
+
+        // The checksum of the whole file goes with the final chunk
+        let checksum = last.then(|| format!("{:x}", hasher.clone().finalize()));
+        if compress {
+            data = gzip_compress(&data)?;
+        }
+        let chunk = MessageType::FileChunk {
+            name: name.clone(),
+            seq,
+            last,
+            data,
+            checksum,
+            compressed: compress,
+        };
+        send_framed(stream, &chunk)
+            .await
+            .with_context(|| format!("Failed to send chunk {} of file: {}", seq, path))?;
+        on_progress(sent, total);
+
+        if last {
+            return Ok(());
+        }
+        data = next;
+        seq += 1;
+    }
+}
+
+/// Returns the name a file is sent under: only its file name, as the directories of the sender
+/// mean nothing to the receiver.
+fn file_name(path: &str) -> Result<String> {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .with_context(|| format!("Not a file path: {}", path))
+}
+
+/// Reads up to `chunk_size` bytes from `file`; fewer bytes are only returned at the end of the
+/// file.
+async fn read_chunk(file: &mut tokio::fs::File, chunk_size: usize, path: &str) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(chunk_size);
+    file.take(chunk_size as u64)
+        .read_to_end(&mut data)
+        .await
+        .with_context(|| format!("Failed to read file: {}", path))?;
+    Ok(data)
+}
+
+/// # File Message
+///
+/// This asynchronous function reads the file at `path` and wraps it in a `MessageType::File`, or
+/// in a `MessageType::CompressedFile` with gzip-compressed content when `compress` is set.
+///
+/// # Arguments
+///
+/// * `path`     - A string slice representing the path to the file.
+/// * `name`     - The name the receiver saves the file under.
+/// * `compress` - Whether the file content is gzip-compressed.
+///
+/// # Returns
+///
+/// A `Result` containing the message, or an `anyhow::Error` if the file can't be read.
+pub async fn file_message(path: &str, name: &str, compress: bool) -> Result<MessageType> {
     let mut file = tokio::fs::File::open(path)
         .await
         .with_context(|| format!("Failed to open file: {}", path))?;
@@ -48,39 +355,434 @@ pub async fn send_file(stream: &mut TcpStream, path: &str) -> Result<(), anyhow:
         .await
         .with_context(|| format!("Failed to read file: {}", path))?;
 
-    let message = MessageType::File(path.to_string(), content);
-    let serialized_message = bincode::serialize(&message)
+    let checksum = sha256_hex(&content);
+    if compress {
+        Ok(MessageType::CompressedFile(
+            name.to_string(),
+            gzip_compress(&content)?,
+            checksum,
+        ))
+    } else {
+        Ok(MessageType::File(name.to_string(), content, checksum))
+    }
+}
+
+/// # Send Directory
+///
+/// This asynchronous function sends every file below the directory at `path`, each as a
+/// `MessageType::File` (or `MessageType::CompressedFile` when `compress` is set) named by its path
+/// relative to the directory, so the receiver can recreate the tree. Path components are joined
+/// with `/` whatever the platform of the sender.
+///
+/// # Arguments
+///
+/// * `stream`   - A mutable reference to the stream representing the communication channel.
+/// * `path`     - A string slice representing the path to the directory to be sent.
+/// * `compress` - Whether the file contents are gzip-compressed before sending.
+///
+/// # Returns
+///
+/// A `Result` containing the number of files sent, or an `anyhow::Error` if the directory can't
+/// be read or a file can't be sent.
+pub async fn send_directory<W>(stream: &mut W, path: &str, compress: bool) -> Result<usize>
+where
+    W: AsyncWrite + Unpin,
+{
+    let root = Path::new(path);
+    let files = directory_files(root)?;
+
+    for relative in &files {
+        let name = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let file_path = root.join(relative);
+        let message = file_message(&file_path.to_string_lossy(), &name, compress).await?;
+        send_framed(stream, &message)
+            .await
+            .with_context(|| format!("Failed to send file: {}", file_path.display()))?;
+    }
+
+    Ok(files.len())
+}
+
+/// Recursively collects the paths of all files below `directory`, relative to it and sorted.
+fn directory_files(directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(directory)
+        .with_context(|| format!("Failed to read directory {}", directory.display()))?;
+
+    for entry in entries {
+        let entry = entry?;
+        let name = PathBuf::from(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            let nested = directory_files(&entry.path())?;
+            files.extend(nested.into_iter().map(|path| name.join(path)));
+        } else {
+            files.push(name);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// # Gzip Compress
+///
+/// Compresses `content` with gzip.
+pub fn gzip_compress(content: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(content)
+        .context("Failed to compress content")?;
+    encoder.finish().context("Failed to compress content")
+}
+
+/// # Gzip Decompress
+///
+/// Decompresses gzip-compressed `content`. Inflating stops after `max_len` bytes, so a small
+/// payload that decompresses to gigabytes (a gzip bomb) is refused instead of exhausting memory.
+pub fn gzip_decompress(content: &[u8], max_len: usize) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(content)
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .context("Failed to decompress content")?;
+    if decompressed.len() > max_len {
+        bail!("Decompressed content is larger than {} bytes", max_len);
+    }
+    Ok(decompressed)
+}
+
+/// # Send Framed
+///
+/// This asynchronous function serializes a message in the selected `message_format` and sends it
+/// over the stream as a single length-prefixed frame. All senders go through this function so the
+/// framing stays consistent with `receive_message`.
+///
+/// # Arguments
+///
+/// * `stream`  - A mutable reference to the stream representing the communication channel, e.g. a
+///   `TcpStream` or its write half.
+/// * `message` - The `MessageType` to be sent.
+///
+/// # Returns
+///
+/// A `Result` indicating success or an `anyhow::Error` if an error occurs during the process.
+pub async fn send_framed<W>(stream: &mut W, message: &MessageType) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let serialized_message = serialize_message(message, message_format())
         .with_context(|| format!("Failed to serialize message: {:?}", message))?;
 
-    stream
-        .write_all(&serialized_message)
+    write_framed(stream, &serialized_message).await
+}
+
+/// The serialization format of messages on the wire: `bincode` by default, `msgpack` when built
+/// with the `msgpack` feature. Server and client have to be built with the same one.
+#[cfg(not(feature = "msgpack"))]
+pub const WIRE_FORMAT: &str = "bincode";
+#[cfg(feature = "msgpack")]
+pub const WIRE_FORMAT: &str = "msgpack";
+
+/// The error returned when a payload can't be decoded in the `WIRE_FORMAT`.
+#[cfg(not(feature = "msgpack"))]
+pub type BinaryDecodeError = bincode::Error;
+#[cfg(feature = "msgpack")]
+pub type BinaryDecodeError = rmp_serde::decode::Error;
+
+/// The error returned when a payload isn't a valid message in the format it is read in.
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error(transparent)]
+    Binary(#[from] BinaryDecodeError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// # Message Format
+///
+/// The serialization of messages inside the length framing: the compact `WIRE_FORMAT` by default,
+/// or JSON, which makes captured traffic human-readable during development.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    /// The `WIRE_FORMAT`, `bincode` or `msgpack`.
+    #[default]
+    Binary,
+    /// One JSON document per frame.
+    Json,
+}
+
+impl MessageFormat {
+    /// Names of the formats accepted by `MessageFormat::parse`.
+    pub const NAMES: [&'static str; 2] = [WIRE_FORMAT, "json"];
+
+    /// Parses a message format from its name, the `WIRE_FORMAT` or `json`.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            WIRE_FORMAT => Ok(MessageFormat::Binary),
+            "json" => Ok(MessageFormat::Json),
+            _ => bail!("Unsupported message format: {}", name),
+        }
+    }
+
+    /// The name of the format, as reported in the `Capabilities`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageFormat::Binary => WIRE_FORMAT,
+            MessageFormat::Json => "json",
+        }
+    }
+
+    /// The byte order of the numbers in the format, as reported in the `Capabilities`: bincode
+    /// writes them little-endian, MessagePack big-endian, and JSON as text.
+    pub fn endianness(self) -> &'static str {
+        match self {
+            #[cfg(not(feature = "msgpack"))]
+            MessageFormat::Binary => "little",
+            #[cfg(feature = "msgpack")]
+            MessageFormat::Binary => "big",
+            MessageFormat::Json => "n/a",
+        }
+    }
+}
+
+static MESSAGE_FORMAT: OnceLock<MessageFormat> = OnceLock::new();
+
+/// Selects the format `send_framed` and `receive_message` use for the rest of the process. It has
+/// to be called at startup, before the first message is sent, and both ends of a connection have
+/// to select the same format.
+///
+/// # Returns
+///
+/// An error if a format has already been selected.
+pub fn set_message_format(format: MessageFormat) -> Result<()> {
+    MESSAGE_FORMAT
+        .set(format)
+        .map_err(|_| anyhow!("The message format has already been selected"))
+}
+
+/// The format selected with `set_message_format`, `MessageFormat::Binary` if none was.
+pub fn message_format() -> MessageFormat {
+    MESSAGE_FORMAT.get().copied().unwrap_or_default()
+}
+
+/// # Serialize Message
+///
+/// Serializes a message into a payload in the given format, without the length framing.
+///
+/// # Arguments
+///
+/// * `message` - The `MessageType` to be serialized.
+/// * `format`  - The `MessageFormat` to serialize it in.
+///
+/// # Returns
+///
+/// The serialized bytes, or an error if the message can't be serialized.
+pub fn serialize_message(message: &MessageType, format: MessageFormat) -> Result<Vec<u8>> {
+    match format {
+        MessageFormat::Binary => encode_message(message),
+        MessageFormat::Json => Ok(serde_json::to_vec(message)?),
+    }
+}
+
+/// # Deserialize Message
+///
+/// Deserializes a payload in the given format, without the length framing, into a message.
+///
+/// # Arguments
+///
+/// * `payload` - The serialized message.
+/// * `format`  - The `MessageFormat` the payload is in.
+///
+/// # Returns
+///
+/// The `MessageType`, or a `DecodeError` if the payload isn't a valid message.
+pub fn deserialize_message(
+    payload: &[u8],
+    format: MessageFormat,
+) -> Result<MessageType, DecodeError> {
+    match format {
+        MessageFormat::Binary => Ok(decode_message(payload)?),
+        MessageFormat::Json => Ok(serde_json::from_slice(payload)?),
+    }
+}
+
+/// # Encode Message
+///
+/// Serializes a message into a payload in the `WIRE_FORMAT`, without the length framing.
+///
+/// # Arguments
+///
+/// * `message` - The `MessageType` to be serialized.
+///
+/// # Returns
+///
+/// The serialized bytes, or an error if the message can't be serialized.
+pub fn encode_message(message: &MessageType) -> Result<Vec<u8>> {
+    #[cfg(not(feature = "msgpack"))]
+    let serialized = bincode::serialize(message)?;
+    // Named fields keep the payload readable by MessagePack libraries of other languages
+    #[cfg(feature = "msgpack")]
+    let serialized = rmp_serde::to_vec_named(message)?;
+    Ok(serialized)
+}
+
+/// # Decode Message
+///
+/// Deserializes a payload in the `WIRE_FORMAT`, without the length framing, into a message.
+///
+/// # Arguments
+///
+/// * `payload` - The serialized message.
+///
+/// # Returns
+///
+/// The `MessageType`, or a `BinaryDecodeError` if the payload isn't a valid message.
+pub fn decode_message(payload: &[u8]) -> Result<MessageType, BinaryDecodeError> {
+    #[cfg(not(feature = "msgpack"))]
+    return bincode::deserialize(payload);
+    #[cfg(feature = "msgpack")]
+    return rmp_serde::from_slice(payload);
+}
+
+/// # Write Framed
+///
+/// This asynchronous function writes a single frame to the stream: the payload length as a 4-byte
+/// big-endian integer, followed by the payload itself. This is the framing `receive_message`
+/// expects on the other end.
+///
+/// # Arguments
+///
+/// * `writer`  - A mutable reference to the stream the frame is written to.
+/// * `payload` - The serialized message bytes.
+///
+/// # Returns
+///
+/// A `Result` indicating success or an `anyhow::Error` if the payload is too large to frame or
+/// the write fails.
+pub async fn write_framed<W>(writer: &mut W, payload: &[u8]) -> Result<(), anyhow::Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    let len = u32::try_from(payload.len())
+        .with_context(|| format!("Message too large to frame: {} bytes", payload.len()))?;
+
+    writer
+        .write_all(&len.to_be_bytes())
+        .await
+        .context("Failed to write message length")?;
+    writer
+        .write_all(payload)
         .await
-        .with_context(|| format!("Failed to send file: {}", path))?;
+        .context("Failed to write message payload")?;
 
     Ok(())
 }
 
+/// # Receive Error
+///
+/// The ways receiving a message with `receive_message` can fail.
+#[derive(Debug, Error)]
+pub enum ReceiveError {
+    /// The peer closed the connection before a new message started.
+    #[error("Connection closed")]
+    Closed,
+    /// Reading from the stream failed, including a connection closed in the middle of a message.
+    #[error("Failed to read message: {0}")]
+    Io(#[from] std::io::Error),
+    /// A message with a length of zero was announced.
+    #[error("Empty message received")]
+    Empty,
+    /// A message longer than the allowed maximum was announced.
+    #[error("Message length {len} exceeds the maximum of {max} bytes")]
+    TooLarge { len: usize, max: usize },
+    /// The message content isn't a valid `MessageType`.
+    #[error("Failed to decode message: {0}")]
+    Decode(#[from] DecodeError),
+}
+
 /// # Receive Message
 ///
-/// This asynchronous function receives a message from the server over a TCP stream. It first reads
-/// the length of the message, then reads the message content, deserializes it using `bincode`, and
-/// returns the deserialized `MessageType`.
+/// This asynchronous function receives a message from the other end of a stream, e.g. a TCP
+/// connection. It first reads the length of the message, then reads the message content,
+/// deserializes it in the selected `message_format`, and returns the deserialized `MessageType`.
+///
+/// # Arguments
+///
+/// * `stream` - A mutable reference to the stream representing the communication channel, e.g. a
+///   `TcpStream` or its read half.
+///
+/// # Returns
+///
+/// A `Result` containing the deserialized `MessageType` if successful, or a `ReceiveError`
+/// describing why no message could be received.
+pub async fn receive_message<R>(stream: &mut R) -> Result<MessageType, ReceiveError>
+where
+    R: AsyncRead + Unpin,
+{
+    receive_message_with_limit(stream, MAX_MESSAGE_LEN).await
+}
+
+/// # Receive Message with Limit
+///
+/// This asynchronous function works like `receive_message`, but rejects messages longer than
+/// `max_len` bytes instead of `MAX_MESSAGE_LEN`.
 ///
 /// # Arguments
 ///
-/// * `stream` - A mutable reference to a `TcpStream` representing the communication channel with
-///              the server.
+/// * `stream`  - A mutable reference to the stream representing the communication channel.
+/// * `max_len` - The maximum accepted message length in bytes.
 ///
 /// # Returns
 ///
-/// An `Option` containing the deserialized `MessageType` if successful, or `None` if an error
-/// occurs during the process.
-pub async fn receive_message(stream: &mut TcpStream) -> Option<MessageType> {
+/// A `Result` containing the deserialized `MessageType` if successful, or a `ReceiveError`
+/// describing why no message could be received.
+pub async fn receive_message_with_limit<R>(
+    stream: &mut R,
+    max_len: usize,
+) -> Result<MessageType, ReceiveError>
+where
+    R: AsyncRead + Unpin,
+{
+    receive_message_into(stream, &mut Vec::new(), max_len).await
+}
+
+/// # Receive Message into a Buffer
+///
+/// This asynchronous function works like `receive_message_with_limit`, but reads the message
+/// content into `buffer` instead of a newly allocated one. The buffer is resized to the length of
+/// each message and keeps its capacity, so a connection receiving many messages reuses a single
+/// allocation as large as its largest message so far.
+///
+/// # Arguments
+///
+/// * `stream`  - A mutable reference to the stream representing the communication channel.
+/// * `buffer`  - The buffer the message content is read into; its previous content is discarded.
+/// * `max_len` - The maximum accepted message length in bytes.
+///
+/// # Returns
+///
+/// A `Result` containing the deserialized `MessageType` if successful, or a `ReceiveError`
+/// describing why no message could be received.
+pub async fn receive_message_into<R>(
+    stream: &mut R,
+    buffer: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<MessageType, ReceiveError>
+where
+    R: AsyncRead + Unpin,
+{
     let mut len_bytes = [0u8; 4];
 
+    // Running out of data before a message starts is a clean end of the connection
     if let Err(err) = stream.read_exact(&mut len_bytes).await {
-        log_error(err);
-        return None;
+        return match err.kind() {
+            std::io::ErrorKind::UnexpectedEof => Err(ReceiveError::Closed),
+            _ => Err(ReceiveError::Io(err)),
+        };
     }
 
     let len = u32::from_be_bytes(len_bytes) as usize;
@@ -88,52 +790,79 @@ pub async fn receive_message(stream: &mut TcpStream) -> Option<MessageType> {
     log_info(&format!("Received message length: {}", len));
 
     if len == 0 {
-        log_info("Empty message received");
-        return None;
+        return Err(ReceiveError::Empty);
     }
-
-    let mut buffer = vec![0u8; len];
-
-    if let Err(err) = stream.read_exact(&mut buffer).await {
-        log_error(err);
-        return None;
+    // Check the announced length before allocating a buffer of that size
+    if len > max_len {
+        return Err(ReceiveError::TooLarge { len, max: max_len });
     }
 
-    match bincode::deserialize(&buffer) {
-        Ok(message) => {
-            log_info(&format!("Received message: {:?}", message));
-            Some(message)
-        }
-        Err(err) => {
-            log_error(err);
-            None
-        }
-    }
+    buffer.clear();
+    buffer.resize(len, 0);
+    stream.read_exact(buffer).await?;
+
+    let message = deserialize_message(buffer, message_format())?;
+    log_info(&format!("Received message: {:?}", message));
+    Ok(message)
 }
 
 /// # Receive File
 ///
 /// This function receives a file from the server and saves it to the local filesystem. The
-/// filename is combined with a timestamp to ensure uniqueness.
+/// filename is combined with a timestamp to ensure uniqueness, and the directory is created if it
+/// doesn't exist yet.
 ///
 /// # Arguments
 ///
 /// * `filename`  - A string slice representing the original filename of the received file.
 /// * `content`   - A slice of bytes containing the content of the received file.
 /// * `directory` - A string representing the directory where the file should be saved.
-pub fn receive_file(filename: &str, content: &[u8], directory: &str) {
-    let timestamp = SystemTime::now()
+///
+/// # Returns
+///
+/// A `Result` containing the path the file was saved at, named `<timestamp>_<filename>`, or an
+/// `anyhow::Error` if the file can't be written.
+pub fn receive_file(filename: &str, content: &[u8], directory: &str) -> Result<PathBuf> {
+    let timestamp = unix_time();
+    let filepath = Path::new(directory).join(format!("{}_{}", timestamp, filename));
+
+    let written =
+        std::fs::create_dir_all(directory).and_then(|_| std::fs::write(&filepath, content));
+    if let Err(err) = written {
+        log_error(&err);
+        return Err(err).with_context(|| format!("Failed to save file at {}", filepath.display()));
+    }
+
+    log_info(&format!("Received file: {}", filepath.display()));
+    Ok(filepath)
+}
+
+/// # Unix Time
+///
+/// Returns the current time as seconds since the unix epoch.
+pub fn unix_time() -> u64 {
+    SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
-        .as_secs();
-    let filepath = format!("{}{}_{}", directory, timestamp, filename);
+        .as_secs()
+}
 
-    if let Err(err) = std::fs::write(&filepath, content) {
-        log_error(err);
-        return;
-    }
+/// # SHA-256 Checksum
+///
+/// Computes the SHA-256 digest of `content` and returns it as a lowercase hex string.
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
 
-    log_info(&format!("Received file: {}", filepath));
+/// # Checksum Sidecar Path
+///
+/// Returns the path of the checksum sidecar belonging to `path`, i.e. `path` with
+/// `.sha256` appended to its full file name.
+pub fn checksum_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".");
+    sidecar.push(CHECKSUM_EXTENSION);
+    PathBuf::from(sidecar)
 }
 
 /// # Log Error
@@ -149,3 +878,375 @@ fn log_error<E: Error>(error: E) {
 fn log_info(message: &str) {
     info!("{}", message);
 }
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_socket_address_brackets_ipv6() {
+        assert_eq!(socket_address("::1", 11111), "[::1]:11111");
+        assert_eq!(socket_address("[::1]", 11111), "[::1]:11111");
+        assert_eq!(socket_address("localhost", 11111), "localhost:11111");
+        assert_eq!(socket_address("127.0.0.1", 2000), "127.0.0.1:2000");
+    }
+
+    #[tokio::test]
+    async fn test_send_framed_consecutive_messages() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut sender = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut receiver, _) = listener.accept().await.unwrap();
+
+        send_framed(&mut sender, &MessageType::Text("first".to_string()))
+            .await
+            .unwrap();
+        send_framed(&mut sender, &MessageType::Text("second".to_string()))
+            .await
+            .unwrap();
+
+        for expected in ["first", "second"] {
+            match receive_message(&mut receiver).await {
+                Ok(MessageType::Text(text)) => assert_eq!(text, expected),
+                other => panic!("Unexpected message: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_login_round_trip() {
+        let serialized = bincode::serialize(&MessageType::Login("alice".to_string())).unwrap();
+
+        match bincode::deserialize(&serialized).unwrap() {
+            MessageType::Login(name) => assert_eq!(name, "alice"),
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ping_pong_round_trip() {
+        let ping = bincode::serialize(&MessageType::Ping).unwrap();
+        let pong = bincode::serialize(&MessageType::Pong).unwrap();
+
+        assert!(matches!(
+            bincode::deserialize(&ping).unwrap(),
+            MessageType::Ping
+        ));
+        assert!(matches!(
+            bincode::deserialize(&pong).unwrap(),
+            MessageType::Pong
+        ));
+    }
+
+    #[test]
+    fn test_text_with_time_round_trip() {
+        let sent_at = unix_time();
+        let message = MessageType::TextWithTime("Hello!".to_string(), sent_at);
+        let serialized = bincode::serialize(&message).unwrap();
+
+        match bincode::deserialize(&serialized).unwrap() {
+            MessageType::TextWithTime(text, timestamp) => {
+                assert_eq!(text, "Hello!");
+                assert_eq!(timestamp, sent_at);
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_text_json_round_trip() {
+        let message = MessageType::Text("Hello, \"server\"!".to_string());
+        let serialized = serialize_message(&message, MessageFormat::Json).unwrap();
+        assert_eq!(serialized, br#"{"Text":"Hello, \"server\"!"}"#);
+
+        match deserialize_message(&serialized, MessageFormat::Json).unwrap() {
+            MessageType::Text(text) => assert_eq!(text, "Hello, \"server\"!"),
+            other => panic!("Unexpected message: {:?}", other),
+        }
+        assert!(matches!(
+            deserialize_message(&serialized, MessageFormat::Binary),
+            Err(DecodeError::Binary(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_message_format() {
+        assert_eq!(
+            MessageFormat::parse(WIRE_FORMAT).unwrap(),
+            MessageFormat::Binary
+        );
+        assert_eq!(MessageFormat::parse("json").unwrap(), MessageFormat::Json);
+        assert!(MessageFormat::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let content = b"Large text files waste bandwidth as raw bytes. ".repeat(100);
+
+        let compressed = gzip_compress(&content).unwrap();
+        assert!(compressed.len() < content.len());
+        assert_eq!(
+            gzip_decompress(&compressed, content.len()).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_gzip_decompress_stops_at_limit() {
+        // A kilobyte of compressed zeros inflates to a megabyte
+        let compressed = gzip_compress(&vec![0; 1024 * 1024]).unwrap();
+        assert!(compressed.len() < 2048);
+
+        let err = gzip_decompress(&compressed, 64 * 1024).unwrap_err();
+        assert!(
+            err.to_string().contains("larger than 65536 bytes"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_capabilities_table() {
+        let capabilities = Capabilities {
+            compression: true,
+            encryption: false,
+            ..Capabilities::default()
+        };
+
+        let table = capabilities.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "| capability  | value   | ");
+        assert_eq!(lines[1], "|-------------|---------|");
+        assert!(lines.contains(&"| compression | on      | "));
+        assert!(lines.contains(&"| encryption  | off     | "));
+    }
+
+    #[tokio::test]
+    async fn test_write_framed_round_trip() {
+        let (mut writer, mut reader) = tokio::io::duplex(1024);
+
+        let message = MessageType::Text("Hello, server!".to_string());
+        let payload = encode_message(&message).unwrap();
+        write_framed(&mut writer, &payload).await.unwrap();
+
+        // Read the frame back the same way receive_message does
+        let mut len_bytes = [0u8; 4];
+        reader.read_exact(&mut len_bytes).await.unwrap();
+        let mut buffer = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+        reader.read_exact(&mut buffer).await.unwrap();
+
+        match decode_message(&buffer).unwrap() {
+            MessageType::Text(text) => assert_eq!(text, "Hello, server!"),
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn test_msgpack_framed_round_trip() {
+        let messages = [
+            MessageType::Text("Hello, server!".to_string()),
+            MessageType::TextWithTime("Hello!".to_string(), unix_time()),
+            MessageType::Ping,
+        ];
+        let mut payload = Vec::new();
+        for message in &messages {
+            send_framed(&mut payload, message).await.unwrap();
+        }
+
+        // The frames carry MessagePack, which bincode can't make sense of
+        let len = u32::from_be_bytes(payload[..4].try_into().unwrap()) as usize;
+        let first = &payload[4..4 + len];
+        assert!(rmp_serde::from_slice::<MessageType>(first).is_ok());
+        assert!(bincode::deserialize::<MessageType>(first).is_err());
+
+        let mut reader = payload.as_slice();
+        for message in messages {
+            let received = receive_message(&mut reader).await.unwrap();
+            assert_eq!(format!("{:?}", received), format!("{:?}", message));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_file_chunked_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("progress.bin");
+        std::fs::write(&path, vec![0u8; 2500]).unwrap();
+
+        let mut updates = Vec::new();
+        send_file_chunked(
+            &mut tokio::io::sink(),
+            path.to_str().unwrap(),
+            1000,
+            false,
+            |sent, total| updates.push((sent, total)),
+        )
+        .await
+        .unwrap();
+
+        // One update per chunk, the last one covering the whole file
+        assert_eq!(updates, vec![(1000, 2500), (2000, 2500), (2500, 2500)]);
+    }
+
+    #[test]
+    fn test_receive_file_creates_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested").join("files");
+
+        receive_file("test.txt", b"Test content", nested.to_str().unwrap()).unwrap();
+
+        assert_eq!(std::fs::read_dir(&nested).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_receive_file_returns_written_path() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let path = receive_file("test.txt", b"Test content", dir.path().to_str().unwrap()).unwrap();
+
+        assert!(path.exists());
+        assert_eq!(path.parent(), Some(dir.path()));
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let (timestamp, filename) = name.split_once('_').unwrap();
+        assert!(timestamp.parse::<u64>().is_ok());
+        assert_eq!(filename, "test.txt");
+        assert_eq!(std::fs::read(&path).unwrap(), b"Test content");
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_reports_closed_connection() {
+        let mut stream = tokio_test::io::Builder::new().build();
+
+        assert!(matches!(
+            receive_message(&mut stream).await,
+            Err(ReceiveError::Closed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_reports_io_error() {
+        let mut stream = tokio_test::io::Builder::new()
+            .read_error(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "reset",
+            ))
+            .build();
+
+        assert!(matches!(
+            receive_message(&mut stream).await,
+            Err(ReceiveError::Io(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_reports_empty_message() {
+        let mut stream = tokio_test::io::Builder::new().read(&[0, 0, 0, 0]).build();
+
+        assert!(matches!(
+            receive_message(&mut stream).await,
+            Err(ReceiveError::Empty)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_reports_decode_error() {
+        // A variant index far beyond the variants of MessageType
+        let mut stream = tokio_test::io::Builder::new()
+            .read(&[0, 0, 0, 4])
+            .read(&[0xFF, 0xFF, 0xFF, 0xFF])
+            .build();
+
+        assert!(matches!(
+            receive_message(&mut stream).await,
+            Err(ReceiveError::Decode(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_rejects_oversized_length() {
+        let mut stream = tokio_test::io::Builder::new()
+            .read(&[0xFF, 0xFF, 0xFF, 0xFF])
+            .build();
+
+        match receive_message(&mut stream).await {
+            Err(ReceiveError::TooLarge { len, max }) => {
+                assert_eq!(len, u32::MAX as usize);
+                assert_eq!(max, MAX_MESSAGE_LEN);
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_with_limit() {
+        let mut payload = Vec::new();
+        let message = MessageType::Text("Hello, server!".to_string());
+        write_framed(&mut payload, &encode_message(&message).unwrap())
+            .await
+            .unwrap();
+
+        let mut reader = payload.as_slice();
+        assert!(matches!(
+            receive_message_with_limit(&mut reader, 8).await,
+            Err(ReceiveError::TooLarge { max: 8, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_into_reuses_buffer() {
+        let mut payload = Vec::new();
+        send_framed(&mut payload, &MessageType::Text("x".repeat(1024)))
+            .await
+            .unwrap();
+        send_framed(&mut payload, &MessageType::Text("short".to_string()))
+            .await
+            .unwrap();
+
+        let mut reader = payload.as_slice();
+        let mut buffer = Vec::new();
+        receive_message_into(&mut reader, &mut buffer, MAX_MESSAGE_LEN)
+            .await
+            .unwrap();
+        let (capacity, allocation) = (buffer.capacity(), buffer.as_ptr());
+
+        // The shorter second message fits into the allocation of the first one
+        let message = receive_message_into(&mut reader, &mut buffer, MAX_MESSAGE_LEN)
+            .await
+            .unwrap();
+        assert!(matches!(message, MessageType::Text(text) if text == "short"));
+        assert_eq!((buffer.capacity(), buffer.as_ptr()), (capacity, allocation));
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_from_in_memory_reader() {
+        let payload = encode_message(&MessageType::Login("alice".to_string())).unwrap();
+        let mut stream = tokio_test::io::Builder::new()
+            .read(&(payload.len() as u32).to_be_bytes())
+            .read(&payload)
+            .build();
+
+        match receive_message(&mut stream).await {
+            Ok(MessageType::Login(name)) => assert_eq!(name, "alice"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receive_message_truncated_frame() {
+        let payload = encode_message(&MessageType::Text("Hello".to_string())).unwrap();
+        let mut frame = (payload.len() as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(&payload[..payload.len() - 2]);
+
+        // The connection ends in the middle of the message, which isn't a clean close
+        let mut reader = frame.as_slice();
+        match receive_message(&mut reader).await {
+            Err(ReceiveError::Io(err)) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof)
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
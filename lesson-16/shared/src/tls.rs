@@ -0,0 +1,128 @@
+// shared/src/tls.rs
+
+//! # TLS
+//!
+//! Setup of the rustls configurations for encrypted connections. The server wraps accepted
+//! streams with a `TlsAcceptor` built from a PEM certificate chain and private key, and the client
+//! wraps its connection with a `TlsConnector`. The framed messages are sent over the TLS stream
+//! just like over a plain `TcpStream`.
+
+use std::{fs::File, io::BufReader, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use tokio_rustls::{
+    rustls::{
+        Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerConfig,
+        ServerName,
+    },
+    TlsAcceptor, TlsConnector,
+};
+
+pub use tokio_rustls::rustls;
+
+/// Loads the certificate chain and private key of a server from PEM files.
+///
+/// # Arguments
+///
+/// * `cert_path` - The path of the PEM file with the certificate chain, leaf certificate first.
+/// * `key_path` - The path of the PEM file with the PKCS#8, RSA or SEC1 private key.
+///
+/// # Returns
+///
+/// A `Result` containing the server configuration, or an `anyhow::Error` if a file can't be read
+/// or doesn't contain a valid certificate or key.
+pub fn server_config(cert_path: &str, key_path: &str) -> Result<Arc<ServerConfig>> {
+    let certs = load_certs(cert_path)?;
+    let key = load_key(key_path)?;
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .with_context(|| {
+            format!(
+                "Invalid certificate or key in {} and {}",
+                cert_path, key_path
+            )
+        })?;
+    Ok(Arc::new(config))
+}
+
+/// Creates the acceptor wrapping accepted streams of a server with TLS.
+pub fn acceptor(config: Arc<ServerConfig>) -> TlsAcceptor {
+    TlsAcceptor::from(config)
+}
+
+/// Creates the connector wrapping the connection of a client with TLS.
+///
+/// # Arguments
+///
+/// * `ca_path` - The path of a PEM file with the certificates to trust, e.g. the self-signed
+///   certificate of the server. The well-known web root certificates are trusted when `None`.
+///
+/// # Returns
+///
+/// A `Result` containing the connector, or an `anyhow::Error` if the file can't be read or
+/// doesn't contain a valid certificate.
+pub fn connector(ca_path: Option<&str>) -> Result<TlsConnector> {
+    let mut roots = RootCertStore::empty();
+    match ca_path {
+        Some(ca_path) => {
+            for cert in load_certs(ca_path)? {
+                roots
+                    .add(&cert)
+                    .with_context(|| format!("Invalid certificate in {}", ca_path))?;
+            }
+        }
+        None => roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints,
+            )
+        })),
+    }
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Parses the host name or IP address the certificate of the server is checked against.
+pub fn server_name(host: &str) -> Result<ServerName> {
+    // IPv6 addresses may be given in brackets, as in socket addresses
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    ServerName::try_from(host).with_context(|| format!("Invalid server name: {}", host))
+}
+
+/// Reads all certificates from a PEM file.
+fn load_certs(path: &str) -> Result<Vec<Certificate>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .with_context(|| format!("Failed to read certificates from {}", path))?;
+    if certs.is_empty() {
+        bail!("No certificates found in {}", path);
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+/// Reads the first private key from a PEM file.
+fn load_key(path: &str) -> Result<PrivateKey> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+    let mut reader = BufReader::new(file);
+    loop {
+        match rustls_pemfile::read_one(&mut reader)
+            .with_context(|| format!("Failed to read private key from {}", path))?
+        {
+            Some(
+                rustls_pemfile::Item::PKCS8Key(key)
+                | rustls_pemfile::Item::RSAKey(key)
+                | rustls_pemfile::Item::ECKey(key),
+            ) => return Ok(PrivateKey(key)),
+            Some(_) => continue,
+            None => bail!("No private key found in {}", path),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// shared/tests/tls.rs
+
+//! Integration test of the framing over a TLS connection secured with a self-signed certificate.
+
+use shared::{receive_message, send_framed, tls, MessageType};
+use tokio::net::{TcpListener, TcpStream};
+
+#[tokio::test]
+async fn test_framed_message_round_trips_over_tls() {
+    // Issue a self-signed certificate for localhost and store it as PEM files
+    let dir = tempfile::tempdir().unwrap();
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_path = dir.path().join("cert.pem");
+    let key_path = dir.path().join("key.pem");
+    std::fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+    std::fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
+    let (cert_path, key_path) = (cert_path.to_str().unwrap(), key_path.to_str().unwrap());
+
+    let acceptor = tls::acceptor(tls::server_config(cert_path, key_path).unwrap());
+    let connector = tls::connector(Some(cert_path)).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = acceptor.accept(stream).await.unwrap();
+        let message = receive_message(&mut stream).await.unwrap();
+        send_framed(&mut stream, &MessageType::Ack(1))
+            .await
+            .unwrap();
+        message
+    });
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut stream = connector
+        .connect(tls::server_name("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    send_framed(&mut stream, &MessageType::Text("over TLS".to_string()))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        receive_message(&mut stream).await,
+        Ok(MessageType::Ack(1))
+    ));
+    assert!(matches!(
+        server.await.unwrap(),
+        MessageType::Text(text) if text == "over TLS"
+    ));
+}
+
+#[tokio::test]
+async fn test_untrusted_certificate_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_path = dir.path().join("cert.pem");
+    let key_path = dir.path().join("key.pem");
+    std::fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+    std::fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
+
+    let acceptor = tls::acceptor(
+        tls::server_config(cert_path.to_str().unwrap(), key_path.to_str().unwrap()).unwrap(),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let _ = acceptor.accept(stream).await;
+    });
+
+    // Without the certificate of the server, only the web root certificates are trusted
+    let connector = tls::connector(None).unwrap();
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let result = connector
+        .connect(tls::server_name("localhost").unwrap(), stream)
+        .await;
+    assert!(result.is_err());
+}